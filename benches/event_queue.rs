@@ -0,0 +1,116 @@
+//! Hold model benchmark of the event queue implementations.
+//!
+//! In the hold model, the queue is first filled with `n` events. Then, each operation
+//! pops the next event and schedules a new one at a random time in the future,
+//! which keeps the size of the queue constant. Before measuring, `n` operations are
+//! executed to reach the steady state.
+//!
+//! Each queue is measured with exponential, uniform and bimodal time increments
+//! and with 1,000 to 1,000,000 pending events. The binary heap and the calendar
+//! queue are on par for small queues, the calendar queue wins for large ones,
+//! and the pairing heap pays for its cheap push with a more expensive pop.
+//! See the [`EventQueue`] documentation for a summary of the results.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use simrs::{
+    BinaryHeapQueue, CalendarQueue, Component, ComponentId, Components, EventQueue, PairingHeap,
+    Scheduler, State,
+};
+use std::time::Duration;
+
+struct Noop;
+
+impl Component for Noop {
+    type Event = ();
+    fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+}
+
+/// Xorshift generator, to avoid depending on a random number crate.
+struct Random(u64);
+
+impl Random {
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+/// Time increment distributions used in the hold operations.
+#[derive(Clone, Copy)]
+enum Increment {
+    Exponential,
+    Uniform,
+    Bimodal,
+}
+
+impl Increment {
+    fn sample(self, random: &mut Random) -> Duration {
+        let u = random.next_f64();
+        let secs = match self {
+            Self::Exponential => -(1.0 - u).ln(),
+            Self::Uniform => 2.0 * u,
+            Self::Bimodal => {
+                if random.next_f64() < 0.9 {
+                    0.01 * u
+                } else {
+                    10.0 * u
+                }
+            }
+        };
+        Duration::from_secs_f64(secs)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Exponential => "exponential",
+            Self::Uniform => "uniform",
+            Self::Bimodal => "bimodal",
+        }
+    }
+}
+
+fn bench_queue<Q, F>(c: &mut Criterion, name: &str, make_queue: F)
+where
+    Q: EventQueue + 'static,
+    F: Fn() -> Q,
+{
+    let mut group = c.benchmark_group(format!("hold/{name}"));
+    for increment in [
+        Increment::Exponential,
+        Increment::Uniform,
+        Increment::Bimodal,
+    ] {
+        for size in [1_000, 10_000, 100_000, 1_000_000] {
+            let mut components = Components::default();
+            let component = components.add_component(Noop);
+            let mut scheduler = Scheduler::with_queue(make_queue());
+            let mut random = Random(0x2545_f491_4f6c_dd1d);
+            for _ in 0..size {
+                scheduler.schedule(increment.sample(&mut random), component, ());
+            }
+            // Reach the steady state before measuring.
+            for _ in 0..size {
+                scheduler.pop();
+                scheduler.schedule(increment.sample(&mut random), component, ());
+            }
+            group.bench_function(BenchmarkId::new(increment.name(), size), |b| {
+                b.iter(|| {
+                    scheduler.pop();
+                    scheduler.schedule(increment.sample(&mut random), component, ());
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn hold(c: &mut Criterion) {
+    bench_queue(c, "binary_heap", BinaryHeapQueue::default);
+    bench_queue(c, "calendar", CalendarQueue::default);
+    bench_queue(c, "pairing_heap", PairingHeap::default);
+}
+
+criterion_group!(benches, hold);
+criterion_main!(benches);
@@ -1,7 +1,9 @@
-use simrs::{Component, ComponentId, Executor, Fifo, Key, QueueId, Scheduler, Simulation, State};
+use simrs::{
+    Component, ComponentId, Executor, Fifo, Key, QueueId, Scheduler, Simulation, State,
+    TryComponent,
+};
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[derive(Debug)]
@@ -11,13 +13,13 @@ struct Producer {
     outgoing: QueueId<Fifo<Product>>,
     consumer: ComponentId<ConsumerEvent>,
     produced_count: Key<usize>,
-    messages: Rc<RefCell<Vec<String>>>,
+    messages: Arc<Mutex<Vec<String>>>,
 }
 
 struct Consumer {
     incoming: QueueId<Fifo<Product>>,
     working_on: Key<Option<Product>>,
-    messages: Rc<RefCell<Vec<String>>>,
+    messages: Arc<Mutex<Vec<String>>>,
 }
 
 #[derive(Debug)]
@@ -29,6 +31,18 @@ enum ConsumerEvent {
     Finished,
 }
 
+/// Error signalling that the consumer finished working without having any product.
+#[derive(Debug)]
+struct NothingToFinish;
+
+impl std::fmt::Display for NothingToFinish {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "consumer finished without a product")
+    }
+}
+
+impl std::error::Error for NothingToFinish {}
+
 impl Producer {
     fn produce(&self) -> Product {
         Product
@@ -37,7 +51,7 @@ impl Producer {
         Duration::from_secs(1)
     }
     fn log(&self) {
-        self.messages.borrow_mut().push(String::from("Produced"));
+        self.messages.lock().unwrap().push(String::from("Produced"));
     }
 }
 
@@ -46,7 +60,7 @@ impl Consumer {
         Duration::from_secs(1)
     }
     fn log(&self, _: Product) {
-        self.messages.borrow_mut().push(String::from("Consumed"));
+        self.messages.lock().unwrap().push(String::from("Consumed"));
     }
 }
 
@@ -71,16 +85,17 @@ impl Component for Producer {
     }
 }
 
-impl Component for Consumer {
+impl TryComponent for Consumer {
     type Event = ConsumerEvent;
+    type Error = NothingToFinish;
 
     fn process_event(
-        &self,
+        &mut self,
         self_id: ComponentId<ConsumerEvent>,
         event: &ConsumerEvent,
         scheduler: &mut Scheduler,
         state: &mut State,
-    ) {
+    ) -> Result<(), NothingToFinish> {
         let busy = state.get(self.working_on).unwrap().is_some();
         match event {
             ConsumerEvent::Received => {
@@ -94,13 +109,18 @@ impl Component for Consumer {
                 }
             }
             ConsumerEvent::Finished => {
-                let product = state.get_mut(self.working_on).unwrap().take().unwrap();
+                let product = state
+                    .get_mut(self.working_on)
+                    .unwrap()
+                    .take()
+                    .ok_or(NothingToFinish)?;
                 self.log(product);
                 if state.len(self.incoming) > 0 {
                     scheduler.schedule(Duration::default(), self_id, ConsumerEvent::Received);
                 }
             }
         }
+        Ok(())
     }
 }
 
@@ -166,7 +186,7 @@ Consumed
 10s";
 
 fn main() {
-    let messages = Rc::new(RefCell::new(Vec::<String>::new()));
+    let messages = Arc::new(Mutex::new(Vec::<String>::new()));
     let mut simulation = Simulation::default();
     let queue = simulation.add_queue(Fifo::default());
     let working_on = simulation.state.insert::<Option<Product>>(None);
@@ -187,11 +207,17 @@ fn main() {
     // The above would fail with:                         ^^^^^^^^^^^^^ expected enum `ConsumerEvent`, found struct `ProducerEvent`
     {
         let messages = messages.clone();
-        simulation.execute(Executor::unbound().side_effect(move |sim| {
-            messages
-                .borrow_mut()
-                .push(format!("{:?}", sim.scheduler.time()));
-        }));
+        simulation
+            .execute(Executor::unbound().side_effect(move |sim| {
+                messages
+                    .lock()
+                    .unwrap()
+                    .push(format!("{:?}", sim.scheduler.time()));
+            }))
+            .expect("Simulation failed");
     }
-    assert_eq!(*messages.borrow(), EXPECTED.split('\n').collect::<Vec<_>>());
+    assert_eq!(
+        *messages.lock().unwrap(),
+        EXPECTED.split('\n').collect::<Vec<_>>()
+    );
 }
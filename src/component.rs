@@ -1,21 +1,40 @@
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
 use std::fmt;
+use std::marker::PhantomData;
+use std::time::Duration;
 
-use crate::{generate_next_id, ComponentId, EventEntry, Scheduler, State};
+use crate::{
+    generate_tag, BinaryHeapQueue, ComponentId, EventEntry, EventQueue, MaybeSend, Scheduler,
+    SimError, SimTime, State,
+};
 
-pub trait ProcessEventEntry {
-    fn process_event_entry(&self, entry: EventEntry, scheduler: &mut Scheduler, state: &mut State);
+pub trait ProcessEventEntry<T, Q>: MaybeSend {
+    fn process_event_entry(
+        &mut self,
+        entry: EventEntry<T>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) -> Result<(), SimError>;
+    fn on_start(&mut self, id: ComponentId<()>, scheduler: &mut Scheduler<T, Q>, state: &mut State);
+    fn on_stop(&mut self, id: ComponentId<()>, scheduler: &mut Scheduler<T, Q>, state: &mut State);
 }
 
 /// Interface of a simulation component.
-pub trait Component: ProcessEventEntry {
+///
+/// The component is generic over the simulation time type `T`, which defaults to [`Duration`].
+///
+/// A component processes events through an immutable reference to itself, so any mutable state
+/// must be stored in [`State`]. See [`ComponentMut`] for components that mutate themselves.
+pub trait Component<T: SimTime = Duration, Q: EventQueue<T> = BinaryHeapQueue<T>> {
     /// Type of event the component reacts to.
-    type Event: fmt::Debug + 'static;
+    type Event: fmt::Debug + MaybeSend + 'static;
 
     /// Reacts to `event`. A component has access to the following elements of the simulation:
     /// - `self_id`: This is the ID of this component. This is used to schedule events to itself.
-    ///              This is passed for convenience, as the ID is only known after the component
-    ///              has been already constructed and passed to the simulation.
+    ///   This is passed for convenience, as the ID is only known after the component
+    ///   has been already constructed and passed to the simulation.
     /// - `event`: The occurring event.
     /// - `scheduler`: The scheduler used to access time and schedule new events.
     /// - `state`: The state is used to access queues and values in the value store.
@@ -23,61 +42,455 @@ pub trait Component: ProcessEventEntry {
         &self,
         self_id: ComponentId<Self::Event>,
         event: &Self::Event,
-        scheduler: &mut Scheduler,
+        scheduler: &mut Scheduler<T, Q>,
         state: &mut State,
     );
+
+    /// Called once by [`Simulation::start`](crate::Simulation::start) before the first event
+    /// is processed. It can be used, e.g., to schedule the initial events of the component.
+    /// Does nothing by default.
+    fn on_start(
+        &self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        let _ = (self_id, scheduler, state);
+    }
+
+    /// Called once by [`Simulation::stop`](crate::Simulation::stop) when the simulation
+    /// has finished, e.g., at the end of [`Simulation::execute`](crate::Simulation::execute).
+    /// It can be used, e.g., to record final statistics.
+    /// Does nothing by default.
+    fn on_stop(
+        &self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        let _ = (self_id, scheduler, state);
+    }
+}
+
+/// Interface of a simulation component that can mutate itself when processing events.
+///
+/// It is implemented for every [`Component`]. The components are owned by the [`Components`]
+/// container, so processing an event mutably borrows only the component itself,
+/// while [`Scheduler`] and [`State`] are borrowed separately.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{ComponentId, ComponentMut, Scheduler, Simulation, State};
+/// # use std::time::Duration;
+/// struct Counter {
+///     count: usize,
+/// }
+///
+/// impl ComponentMut for Counter {
+///     type Event = ();
+///     fn process_event(
+///         &mut self,
+///         self_id: ComponentId<()>,
+///         _event: &(),
+///         scheduler: &mut Scheduler,
+///         _state: &mut State,
+///     ) {
+///         self.count += 1;
+///         if self.count < 3 {
+///             scheduler.schedule(Duration::from_secs(1), self_id, ());
+///         }
+///     }
+/// }
+///
+/// let mut simulation = Simulation::default();
+/// let counter = simulation.add_component(Counter { count: 0 });
+/// simulation.schedule(Duration::default(), counter, ());
+/// while simulation.step() {}
+/// assert_eq!(simulation.scheduler.time(), Duration::from_secs(2));
+/// ```
+pub trait ComponentMut<T: SimTime = Duration, Q: EventQueue<T> = BinaryHeapQueue<T>> {
+    /// Type of event the component reacts to.
+    type Event: fmt::Debug + MaybeSend + 'static;
+
+    /// Reacts to `event`. See [`Component::process_event`] for the description of the arguments.
+    fn process_event(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        event: &Self::Event,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    );
+
+    /// Called before the first event is processed. See [`Component::on_start`].
+    fn on_start(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        let _ = (self_id, scheduler, state);
+    }
+
+    /// Called once the simulation has finished. See [`Component::on_stop`].
+    fn on_stop(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        let _ = (self_id, scheduler, state);
+    }
 }
 
-impl<E, C> ProcessEventEntry for C
+impl<T, Q, C> ComponentMut<T, Q> for C
 where
-    E: fmt::Debug + 'static,
-    C: Component<Event = E>,
+    T: SimTime,
+    Q: EventQueue<T>,
+    C: Component<T, Q>,
 {
-    fn process_event_entry(&self, entry: EventEntry, scheduler: &mut Scheduler, state: &mut State) {
+    type Event = C::Event;
+
+    fn process_event(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        event: &Self::Event,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        Component::process_event(self, self_id, event, scheduler, state);
+    }
+
+    fn on_start(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        Component::on_start(self, self_id, scheduler, state);
+    }
+
+    fn on_stop(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        Component::on_stop(self, self_id, scheduler, state);
+    }
+}
+
+/// Interface of a simulation component whose event processing can fail.
+///
+/// It is implemented for every [`ComponentMut`] (and thus every [`Component`]) with
+/// [`Infallible`] error, and it is what the [`Components`] container actually stores.
+/// An error returned from [`TryComponent::process_event`] halts the execution, and it is
+/// reported by [`Simulation::try_step`](crate::Simulation::try_step) and
+/// [`Simulation::execute`](crate::Simulation::execute) along with the time and the component ID.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{ComponentId, Executor, Scheduler, Simulation, SimError, State, TryComponent};
+/// # use std::time::Duration;
+/// #[derive(Debug)]
+/// struct Overheated;
+///
+/// impl std::fmt::Display for Overheated {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "machine overheated")
+///     }
+/// }
+///
+/// impl std::error::Error for Overheated {}
+///
+/// struct Machine {
+///     temperature: u32,
+/// }
+///
+/// impl TryComponent for Machine {
+///     type Event = ();
+///     type Error = Overheated;
+///     fn process_event(
+///         &mut self,
+///         self_id: ComponentId<()>,
+///         _event: &(),
+///         scheduler: &mut Scheduler,
+///         _state: &mut State,
+///     ) -> Result<(), Overheated> {
+///         self.temperature += 10;
+///         if self.temperature > 100 {
+///             return Err(Overheated);
+///         }
+///         scheduler.schedule(Duration::from_secs(1), self_id, ());
+///         Ok(())
+///     }
+/// }
+///
+/// let mut simulation = Simulation::default();
+/// let machine = simulation.add_component(Machine { temperature: 60 });
+/// simulation.schedule(Duration::default(), machine, ());
+/// let err = simulation.execute(Executor::unbound()).unwrap_err();
+/// assert_eq!(err.time, Duration::from_secs(4));
+/// assert_eq!(err.component, machine.idx());
+/// assert!(matches!(err.error, SimError::Component { .. }));
+/// ```
+pub trait TryComponent<T: SimTime = Duration, Q: EventQueue<T> = BinaryHeapQueue<T>> {
+    /// Type of event the component reacts to.
+    type Event: fmt::Debug + MaybeSend + 'static;
+
+    /// Type of error returned when processing an event fails.
+    type Error: Error + MaybeSend + 'static;
+
+    /// Reacts to `event`. See [`Component::process_event`] for the description of the arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be processed, which halts the simulation.
+    fn process_event(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        event: &Self::Event,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) -> Result<(), Self::Error>;
+
+    /// Called before the first event is processed. See [`Component::on_start`].
+    fn on_start(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        let _ = (self_id, scheduler, state);
+    }
+
+    /// Called once the simulation has finished. See [`Component::on_stop`].
+    fn on_stop(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        let _ = (self_id, scheduler, state);
+    }
+}
+
+impl<T, Q, C> TryComponent<T, Q> for C
+where
+    T: SimTime,
+    Q: EventQueue<T>,
+    C: ComponentMut<T, Q>,
+{
+    type Event = C::Event;
+    type Error = Infallible;
+
+    fn process_event(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        event: &Self::Event,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) -> Result<(), Infallible> {
+        ComponentMut::process_event(self, self_id, event, scheduler, state);
+        Ok(())
+    }
+
+    fn on_start(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        ComponentMut::on_start(self, self_id, scheduler, state);
+    }
+
+    fn on_stop(
+        &mut self,
+        self_id: ComponentId<Self::Event>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        ComponentMut::on_stop(self, self_id, scheduler, state);
+    }
+}
+
+impl<T, Q, E, C> ProcessEventEntry<T, Q> for C
+where
+    T: SimTime,
+    Q: EventQueue<T>,
+    E: fmt::Debug + MaybeSend + 'static,
+    C: TryComponent<T, Q, Event = E> + MaybeSend,
+{
+    fn process_event_entry(
+        &mut self,
+        entry: EventEntry<T>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) -> Result<(), SimError> {
+        let component = entry.component_idx();
         let entry = entry
             .downcast::<E>()
-            .expect("Failed to downcast event entry.");
-        self.process_event(entry.component_id, entry.event, scheduler, state);
+            .ok_or(SimError::EventTypeMismatch { component })?;
+        self.process_event(entry.component_id, entry.event, scheduler, state)
+            .map_err(|error| SimError::Component {
+                component,
+                error: Box::new(error),
+            })
+    }
+
+    fn on_start(
+        &mut self,
+        id: ComponentId<()>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) {
+        TryComponent::on_start(self, id.cast(), scheduler, state);
+    }
+
+    fn on_stop(&mut self, id: ComponentId<()>, scheduler: &mut Scheduler<T, Q>, state: &mut State) {
+        TryComponent::on_stop(self, id.cast(), scheduler, state);
     }
 }
 
+type BoxedComponent<T, Q> = Box<dyn ProcessEventEntry<T, Q>>;
+
 /// Container holding type-erased components.
-#[derive(Default)]
-pub struct Components {
-    components: HashMap<usize, Box<dyn ::std::any::Any>>,
+///
+/// Component IDs are allocated by the container, and just like keys in [`State`],
+/// they are tagged with the ID space of the simulation.
+#[allow(clippy::struct_field_names)]
+pub struct Components<T = Duration, Q = BinaryHeapQueue<T>> {
+    tag: usize,
+    components: HashMap<usize, BoxedComponent<T, Q>>,
+    next_id: usize,
+    _marker: PhantomData<(T, Q)>,
 }
 
-impl Components {
-    #[allow(clippy::missing_panics_doc)]
+impl<T, Q> Default for Components<T, Q> {
+    fn default() -> Self {
+        Self::with_tag(generate_tag())
+    }
+}
+
+impl<T, Q> Components<T, Q> {
+    /// Creates an empty container issuing component IDs with the given tag.
+    pub(crate) fn with_tag(tag: usize) -> Self {
+        Self {
+            tag,
+            components: HashMap::default(),
+            next_id: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: SimTime, Q: EventQueue<T>> Components<T, Q> {
     /// Process the event on the component given by the event entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event cannot be processed, e.g., the component does not exist.
+    /// See [`Components::try_process_event_entry`] for a fallible version.
     pub fn process_event_entry(
-        &self,
-        entry: EventEntry,
-        scheduler: &mut Scheduler,
+        &mut self,
+        entry: EventEntry<T>,
+        scheduler: &mut Scheduler<T, Q>,
         state: &mut State,
     ) {
+        if let Err(err) = self.try_process_event_entry(entry, scheduler, state) {
+            panic!("Failed to process event: {}", err);
+        }
+    }
+
+    /// Process the event on the component given by the event entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimError::ForeignComponent`] if the component was registered in another
+    /// simulation, [`SimError::MissingComponent`] if the component does not exist, e.g., it has
+    /// been removed, [`SimError::EventTypeMismatch`]
+    /// if the event type does not match the component, and [`SimError::Component`]
+    /// if a [`TryComponent`] failed to process the event.
+    pub fn try_process_event_entry(
+        &mut self,
+        entry: EventEntry<T>,
+        scheduler: &mut Scheduler<T, Q>,
+        state: &mut State,
+    ) -> Result<(), SimError> {
+        let component = entry.component_idx();
+        if entry.component().tag != self.tag {
+            return Err(SimError::ForeignComponent { component });
+        }
         self.components
-            .get(&entry.component_idx())
-            .unwrap()
-            .downcast_ref::<Box<dyn ProcessEventEntry>>()
-            .expect("Failed to downcast component.")
-            .process_event_entry(entry, scheduler, state);
+            .get_mut(&component)
+            .ok_or(SimError::MissingComponent { component })?
+            .process_event_entry(entry, scheduler, state)
     }
 
     /// Registers a new component and returns its ID.
     #[must_use]
-    pub fn add_component<E: fmt::Debug + 'static, C: Component<Event = E> + 'static>(
-        &mut self,
-        component: C,
-    ) -> ComponentId<E> {
-        let id = generate_next_id();
-        let component: Box<dyn ProcessEventEntry> = Box::new(component);
+    pub fn add_component<E, C>(&mut self, component: C) -> ComponentId<E>
+    where
+        E: fmt::Debug + MaybeSend + 'static,
+        C: TryComponent<T, Q, Event = E> + MaybeSend + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
         self.components.insert(id, Box::new(component));
-        ComponentId::new(id)
+        ComponentId::new(self.tag, id)
+    }
+
+    /// Removes the component with the given ID, and cancels all its pending events
+    /// in `scheduler`, including periodic ones.
+    ///
+    /// Returns `true` if the component was registered, and `false` otherwise,
+    /// in which case the scheduler is not modified.
+    pub fn remove_component<E: fmt::Debug + 'static>(
+        &mut self,
+        component: ComponentId<E>,
+        scheduler: &mut Scheduler<T, Q>,
+    ) -> bool {
+        if component.tag != self.tag {
+            return false;
+        }
+        if self.components.remove(&component.idx()).is_none() {
+            return false;
+        }
+        scheduler.retain(|entry| !entry.is_for(component));
+        true
+    }
+
+    /// Calls [`TryComponent::on_start`] on all registered components in the order
+    /// in which they were added.
+    pub fn on_start(&mut self, scheduler: &mut Scheduler<T, Q>, state: &mut State) {
+        for (id, component) in self.sorted_components() {
+            component.on_start(id, scheduler, state);
+        }
+    }
+
+    /// Calls [`TryComponent::on_stop`] on all registered components in the order
+    /// in which they were added.
+    pub fn on_stop(&mut self, scheduler: &mut Scheduler<T, Q>, state: &mut State) {
+        for (id, component) in self.sorted_components() {
+            component.on_stop(id, scheduler, state);
+        }
+    }
+
+    /// Returns the components ordered by their IDs, which makes hook calls deterministic.
+    fn sorted_components(&mut self) -> Vec<(ComponentId<()>, &mut BoxedComponent<T, Q>)> {
+        let tag = self.tag;
+        let mut components: Vec<_> = self
+            .components
+            .iter_mut()
+            .map(|(&id, component)| (ComponentId::new(tag, id), component))
+            .collect();
+        components.sort_unstable_by_key(|(id, _)| id.id);
+        components
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(feature = "sync")))]
 mod test {
     use super::*;
     use std::cell::RefCell;
@@ -95,7 +508,7 @@ mod test {
             _scheduler: &mut Scheduler,
             _state: &mut State,
         ) {
-            *self.0.borrow_mut() = event.clone();
+            self.0.borrow_mut().clone_from(event);
         }
     }
 
@@ -111,7 +524,7 @@ mod test {
             _scheduler: &mut Scheduler,
             _state: &mut State,
         ) {
-            self.borrow_mut().0 = event.clone();
+            self.borrow_mut().0.clone_from(event);
         }
     }
 
@@ -122,17 +535,13 @@ mod test {
         let mut components = Components::default();
         assert_eq!(components.components.len(), 0);
 
-        let text = Rc::new(RefCell::new(String::from("")));
+        let text = Rc::new(RefCell::new(String::new()));
 
         let comp: ComponentId<String> = components.add_component(TestComponent(Rc::clone(&text)));
         assert_eq!(components.components.len(), 1);
 
         components.process_event_entry(
-            EventEntry::new(
-                std::time::Duration::default(),
-                comp,
-                String::from("Modified"),
-            ),
+            EventEntry::new(Duration::default(), comp, String::from("Modified")),
             &mut scheduler,
             &mut state,
         );
@@ -145,20 +554,243 @@ mod test {
         let mut scheduler = Scheduler::default();
         let mut state = State::default();
 
-        let component = Rc::new(RefCell::new(RcTestComponent(String::from(""))));
+        let component = Rc::new(RefCell::new(RcTestComponent(String::new())));
         let mut components = Components::default();
         let comp: ComponentId<String> = components.add_component(Rc::clone(&component));
 
         components.process_event_entry(
-            EventEntry::new(
-                std::time::Duration::default(),
-                comp,
-                String::from("Modified"),
-            ),
+            EventEntry::new(Duration::default(), comp, String::from("Modified")),
             &mut scheduler,
             &mut state,
         );
 
         assert_eq!(component.borrow().0, "Modified");
     }
+
+    struct CountingComponent {
+        events: Vec<String>,
+        output: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl ComponentMut for CountingComponent {
+        type Event = String;
+
+        fn process_event(
+            &mut self,
+            _self_id: ComponentId<Self::Event>,
+            event: &Self::Event,
+            _scheduler: &mut Scheduler,
+            _state: &mut State,
+        ) {
+            self.events.push(event.clone());
+            self.output.borrow_mut().clone_from(&self.events);
+        }
+    }
+
+    #[test]
+    fn test_component_mut() {
+        let mut scheduler = Scheduler::default();
+        let mut state = State::default();
+        let mut components = Components::default();
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let comp = components.add_component(CountingComponent {
+            events: Vec::new(),
+            output: Rc::clone(&output),
+        });
+        for event in ["A", "B"] {
+            components.process_event_entry(
+                EventEntry::new(Duration::default(), comp, String::from(event)),
+                &mut scheduler,
+                &mut state,
+            );
+        }
+        assert_eq!(*output.borrow(), vec![String::from("A"), String::from("B")]);
+    }
+
+    struct LifecycleComponent(Rc<RefCell<Vec<String>>>);
+
+    impl Component for LifecycleComponent {
+        type Event = ();
+
+        fn process_event(
+            &self,
+            _self_id: ComponentId<Self::Event>,
+            _event: &Self::Event,
+            scheduler: &mut Scheduler,
+            _state: &mut State,
+        ) {
+            self.0
+                .borrow_mut()
+                .push(format!("event at {:?}", scheduler.time()));
+        }
+
+        fn on_start(
+            &self,
+            self_id: ComponentId<Self::Event>,
+            scheduler: &mut Scheduler,
+            _state: &mut State,
+        ) {
+            self.0.borrow_mut().push(String::from("start"));
+            scheduler.schedule(Duration::from_secs(1), self_id, ());
+        }
+
+        fn on_stop(
+            &self,
+            _self_id: ComponentId<Self::Event>,
+            _scheduler: &mut Scheduler,
+            _state: &mut State,
+        ) {
+            self.0.borrow_mut().push(String::from("stop"));
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_hooks() {
+        let mut scheduler = Scheduler::default();
+        let mut state = State::default();
+        let mut components = Components::default();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let _ = components.add_component(LifecycleComponent(Rc::clone(&log)));
+        components.on_start(&mut scheduler, &mut state);
+        while let Some(entry) = scheduler.pop() {
+            components.process_event_entry(entry, &mut scheduler, &mut state);
+        }
+        components.on_stop(&mut scheduler, &mut state);
+        assert_eq!(*log.borrow(), vec!["start", "event at 1s", "stop"]);
+    }
+
+    #[test]
+    fn test_remove_component() {
+        let mut scheduler = Scheduler::default();
+        let mut state = State::default();
+        let mut components = Components::default();
+        let removed_text = Rc::new(RefCell::new(String::new()));
+        let kept_text = Rc::new(RefCell::new(String::new()));
+        let removed = components.add_component(TestComponent(Rc::clone(&removed_text)));
+        let kept = components.add_component(TestComponent(Rc::clone(&kept_text)));
+        scheduler.schedule(Duration::from_secs(1), removed, String::from("A"));
+        scheduler.schedule_periodic(
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+            removed,
+            || String::from("B"),
+        );
+        scheduler.schedule(Duration::from_secs(2), kept, String::from("C"));
+
+        assert!(components.remove_component(removed, &mut scheduler));
+        assert_eq!(components.components.len(), 1);
+        assert_eq!(scheduler.pending_count(removed), 0);
+        assert_eq!(scheduler.len(), 1);
+
+        // The scheduler is not modified when the component is not registered.
+        let handle = scheduler.schedule(Duration::from_secs(3), removed, String::from("D"));
+        assert!(!components.remove_component(removed, &mut scheduler));
+        assert_eq!(scheduler.pending_count(removed), 1);
+        assert!(scheduler.cancel(handle));
+
+        while let Some(entry) = scheduler.pop() {
+            components.process_event_entry(entry, &mut scheduler, &mut state);
+        }
+        assert_eq!(*removed_text.borrow(), "");
+        assert_eq!(*kept_text.borrow(), "C");
+    }
+
+    #[test]
+    fn test_try_process_event_entry() {
+        let mut scheduler = Scheduler::default();
+        let mut state = State::default();
+        let mut components = Components::default();
+        let text = Rc::new(RefCell::new(String::new()));
+        let comp: ComponentId<String> = components.add_component(TestComponent(Rc::clone(&text)));
+
+        let wrong_type = EventEntry::new(Duration::default(), comp.cast::<i32>(), 7);
+        assert!(matches!(
+            components.try_process_event_entry(wrong_type, &mut scheduler, &mut state),
+            Err(SimError::EventTypeMismatch { component }) if component == comp.id
+        ));
+
+        let mut other = Components::default();
+        let foreign: ComponentId<String> = other.add_component(TestComponent(Rc::clone(&text)));
+        assert_eq!(foreign.idx(), comp.idx());
+        let entry = EventEntry::new(Duration::default(), foreign, String::from("Foreign"));
+        assert!(matches!(
+            components.try_process_event_entry(entry, &mut scheduler, &mut state),
+            Err(SimError::ForeignComponent { component }) if component == foreign.id
+        ));
+        assert!(!components.remove_component(foreign, &mut scheduler));
+
+        let removed: ComponentId<String> =
+            components.add_component(TestComponent(Rc::clone(&text)));
+        assert!(components.remove_component(removed, &mut scheduler));
+        let missing = EventEntry::new(Duration::default(), removed, String::from("Removed"));
+        assert!(matches!(
+            components.try_process_event_entry(missing, &mut scheduler, &mut state),
+            Err(SimError::MissingComponent { component }) if component == removed.id
+        ));
+
+        let entry = EventEntry::new(Duration::default(), comp, String::from("Modified"));
+        assert!(components
+            .try_process_event_entry(entry, &mut scheduler, &mut state)
+            .is_ok());
+        assert_eq!(*text.borrow(), "Modified");
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod sync_test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    struct SharedComponent(Arc<Mutex<Vec<String>>>);
+
+    impl Component for SharedComponent {
+        type Event = String;
+
+        fn process_event(
+            &self,
+            _self_id: ComponentId<Self::Event>,
+            event: &Self::Event,
+            _scheduler: &mut Scheduler,
+            _state: &mut State,
+        ) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_process_on_another_thread() {
+        let mut scheduler = Scheduler::default();
+        let mut components = Components::default();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let comp = components.add_component(SharedComponent(Arc::clone(&log)));
+        scheduler.schedule(Duration::from_secs(1), comp, String::from("A"));
+        scheduler.schedule(Duration::from_secs(2), comp, String::from("B"));
+        thread::spawn(move || {
+            let mut state = State::default();
+            while let Some(entry) = scheduler.pop() {
+                components.process_event_entry(entry, &mut scheduler, &mut state);
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_remove_component() {
+        let mut scheduler = Scheduler::default();
+        let mut state = State::default();
+        let mut components = Components::default();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let removed = components.add_component(SharedComponent(Arc::clone(&log)));
+        let kept = components.add_component(SharedComponent(Arc::clone(&log)));
+        scheduler.schedule(Duration::from_secs(1), removed, String::from("removed"));
+        scheduler.schedule(Duration::from_secs(1), kept, String::from("kept"));
+        assert!(components.remove_component(removed, &mut scheduler));
+        while let Some(entry) = scheduler.pop() {
+            components.process_event_entry(entry, &mut scheduler, &mut state);
+        }
+        assert_eq!(*log.lock().unwrap(), vec!["kept"]);
+    }
 }
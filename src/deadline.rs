@@ -0,0 +1,394 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::Duration;
+
+use super::{
+    ClockRef, ComponentId, EventHandle, EventQueue, MaybeSend, PushError, Queue, Scheduler, SimTime,
+};
+
+/// Element of a [`DeadlineQueue`] that expired before it was served.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reneged<V, T = Duration> {
+    /// The expired element.
+    pub value: V,
+    /// The time at which the element was pushed to the queue.
+    pub arrival: T,
+    /// The time at which the element expired.
+    pub expiry: T,
+}
+
+/// Element waiting in a [`DeadlineQueue`].
+struct Waiting<V, T> {
+    value: V,
+    patience: T,
+    arrival: T,
+    expiry: T,
+}
+
+impl<V, T> Waiting<V, T> {
+    fn into_reneged(self) -> Reneged<V, T> {
+        Reneged {
+            value: self.value,
+            arrival: self.arrival,
+            expiry: self.expiry,
+        }
+    }
+}
+
+/// Queue whose elements leave on their own (renege) if they are not served before their
+/// deadline, such as customers abandoning a call center queue after their patience runs out.
+///
+/// Elements are pushed along with their patience, and they expire at the current simulation time,
+/// as read from the clock, plus the patience. An element is considered expired at its expiry time.
+/// The elements that have not expired are served in FIFO order. Expired elements are
+/// moved to a list of reneged elements on every push and pop, or when
+/// [`DeadlineQueue::purge_expired`] is called, and they can be retrieved with
+/// [`DeadlineQueue::take_reneged`], e.g., to record statistics. [`Queue::len`] never counts
+/// expired elements, even if they have not been purged yet, so a non-empty queue always
+/// has an element to pop.
+///
+/// Pushing, popping, and purging each expired element take logarithmic time. Computing the length
+/// takes logarithmic time plus the time proportional to the number of expired elements
+/// that have not been purged yet.
+///
+/// The patience is expressed in the simulation time `T`, which defaults to [`Duration`].
+/// The expiry times saturate at the greatest time, see [`SimTime::saturating_add`].
+///
+/// To react to an element reneging, e.g., to record it as soon as it happens, push it with
+/// [`DeadlineQueue::push_with_expiry_event`], which schedules an event at its expiry time.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{DeadlineQueue, Queue, Scheduler};
+/// # use std::time::Duration;
+/// # let secs = Duration::from_secs;
+/// let scheduler = Scheduler::default();
+/// let mut queue = DeadlineQueue::new(scheduler.clock());
+/// queue.push(("impatient", secs(0))).unwrap();
+/// queue.push(("patient", secs(60))).unwrap();
+/// assert_eq!(queue.len(), 1);
+/// assert_eq!(queue.pop(), Some(("patient", secs(60))));
+/// let reneged = queue.take_reneged();
+/// assert_eq!(reneged[0].value, "impatient");
+/// assert_eq!(queue.reneged_count(), 1);
+/// ```
+pub struct DeadlineQueue<V, T = Duration> {
+    clock: ClockRef<T>,
+    /// Waiting elements by their sequence numbers, i.e., in FIFO order.
+    waiting: BTreeMap<u64, Waiting<V, T>>,
+    /// Expiry times and sequence numbers of the waiting elements.
+    expiries: BTreeSet<(T, u64)>,
+    reneged: Vec<Reneged<V, T>>,
+    reneged_count: u64,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl<V, T: SimTime> DeadlineQueue<V, T> {
+    /// Creates a new unbounded queue, using `clock` to determine the expiry times.
+    #[must_use]
+    pub fn new(clock: ClockRef<T>) -> Self {
+        Self::bounded(usize::MAX, clock)
+    }
+
+    /// Creates a new queue with limited capacity. Expired elements are purged before
+    /// the capacity is checked, so they do not count towards it.
+    #[must_use]
+    pub fn bounded(capacity: usize, clock: ClockRef<T>) -> Self {
+        Self {
+            clock,
+            waiting: BTreeMap::new(),
+            expiries: BTreeSet::new(),
+            reneged: Vec::new(),
+            reneged_count: 0,
+            capacity,
+            next_seq: 0,
+        }
+    }
+
+    /// Pushes an element along with its patience, and schedules `event` for `component`
+    /// at the time when the element expires. Returns the handle of the scheduled event.
+    ///
+    /// The event is delivered even if the element has been served in the meantime,
+    /// unless it is cancelled using the handle. When processing it, the component should
+    /// call [`DeadlineQueue::purge_expired`] to find out if any element has actually reneged.
+    ///
+    /// The event is scheduled at the expiry time of the element, which saturates at the greatest
+    /// time, so an unlimited patience, e.g., [`Duration::MAX`], does not overflow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error carrying the element if the queue is full, in which case
+    /// no event is scheduled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expiry time is before the current time of `scheduler`, e.g., when the patience
+    /// is a negative [`OrderedF64`](crate::OrderedF64) or the queue does not read the clock of
+    /// `scheduler`.
+    pub fn push_with_expiry_event<E, Q>(
+        &mut self,
+        (value, patience): (V, T),
+        scheduler: &mut Scheduler<T, Q>,
+        component: ComponentId<E>,
+        event: E,
+    ) -> Result<EventHandle, PushError<(V, T)>>
+    where
+        E: fmt::Debug + MaybeSend + 'static,
+        Q: EventQueue<T>,
+    {
+        let expiry = self.insert(value, patience)?;
+        Ok(scheduler
+            .schedule_at(expiry, component, event)
+            .unwrap_or_else(|err| panic!("cannot schedule expiry event: {}", err)))
+    }
+
+    /// Pushes an element along with its patience, and returns its expiry time.
+    fn insert(&mut self, value: V, patience: T) -> Result<T, PushError<(V, T)>> {
+        self.purge_expired();
+        if self.waiting.len() >= self.capacity {
+            return Err(PushError((value, patience)));
+        }
+        let arrival = self.clock.time();
+        let expiry = arrival.saturating_add(patience);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.expiries.insert((expiry, seq));
+        self.waiting.insert(
+            seq,
+            Waiting {
+                value,
+                patience,
+                arrival,
+                expiry,
+            },
+        );
+        Ok(expiry)
+    }
+
+    /// Moves all expired elements to the list of reneged elements, and returns their number.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.time();
+        let mut count = 0;
+        while let Some(&(expiry, seq)) = self.expiries.first() {
+            if expiry > now {
+                break;
+            }
+            self.expiries.pop_first();
+            if let Some(element) = self.waiting.remove(&seq) {
+                self.reneged_count += 1;
+                self.reneged.push(element.into_reneged());
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns the earliest time at which a waiting element expires, or `None` if there are no
+    /// waiting elements. It may be in the past if some expired elements have not been purged yet.
+    #[must_use]
+    pub fn next_expiry(&self) -> Option<T> {
+        self.expiries.first().map(|&(expiry, _)| expiry)
+    }
+
+    /// Returns the reneged elements that have not been taken yet, in the order of their expiry.
+    #[must_use]
+    pub fn reneged(&self) -> &[Reneged<V, T>] {
+        &self.reneged
+    }
+
+    /// Takes the reneged elements out of the queue.
+    pub fn take_reneged(&mut self) -> Vec<Reneged<V, T>> {
+        std::mem::take(&mut self.reneged)
+    }
+
+    /// Returns the total number of reneged elements, including the ones that have been taken.
+    #[must_use]
+    pub fn reneged_count(&self) -> u64 {
+        self.reneged_count
+    }
+}
+
+impl<V, T: SimTime> Queue for DeadlineQueue<V, T> {
+    /// Element along with its patience.
+    type Item = (V, T);
+
+    fn push(&mut self, (value, patience): (V, T)) -> Result<Option<(V, T)>, PushError<(V, T)>> {
+        self.insert(value, patience).map(|_| None)
+    }
+
+    /// Removes the first element that has not expired, after moving all expired elements
+    /// to the list of reneged elements.
+    fn pop(&mut self) -> Option<(V, T)> {
+        self.purge_expired();
+        let (seq, element) = self.waiting.pop_first()?;
+        self.expiries.remove(&(element.expiry, seq));
+        Some((element.value, element.patience))
+    }
+
+    /// Returns the number of waiting elements that have not expired, whether or not
+    /// the expired ones have been purged.
+    fn len(&self) -> usize {
+        let now = self.clock.time();
+        let expired = self.expiries.range(..=(now, u64::MAX)).count();
+        self.waiting.len() - expired
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Component, Simulation, State};
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_deadline_queue() {
+        let clock = crate::sync::Clock::new(Duration::ZERO);
+        let mut queue = DeadlineQueue::bounded(2, ClockRef::from(clock.clone()));
+        queue.push(('a', secs(5))).unwrap();
+        queue.push(('b', secs(2))).unwrap();
+        assert_eq!(queue.push(('c', secs(1))), Err(PushError(('c', secs(1)))));
+        assert_eq!(queue.next_expiry(), Some(secs(2)));
+
+        clock.set(secs(3));
+        // The expired element is not counted even before it is purged.
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.reneged_count(), 0);
+        // The expired element does not count towards the capacity.
+        queue.push(('c', secs(4))).unwrap();
+        assert_eq!(queue.reneged_count(), 1);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some(('a', secs(5))));
+
+        clock.set(secs(7));
+        assert_eq!(queue.len(), 0);
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+        assert_eq!(queue.reneged_count(), 2);
+        let reneged = queue.take_reneged();
+        assert_eq!(
+            reneged,
+            vec![
+                Reneged {
+                    value: 'b',
+                    arrival: secs(0),
+                    expiry: secs(2)
+                },
+                Reneged {
+                    value: 'c',
+                    arrival: secs(3),
+                    expiry: secs(7)
+                }
+            ]
+        );
+        assert!(queue.reneged().is_empty());
+        assert_eq!(queue.reneged_count(), 2);
+    }
+
+    #[test]
+    fn test_deadline_queue_ticks() {
+        let clock = crate::sync::Clock::new(0_u64);
+        let mut queue = DeadlineQueue::new(ClockRef::from(clock.clone()));
+        queue.push(('a', 2)).unwrap();
+        queue.push(('b', u64::MAX)).unwrap();
+        assert_eq!(queue.next_expiry(), Some(2));
+        clock.set(5);
+        assert_eq!(queue.pop(), Some(('b', u64::MAX)));
+        assert_eq!(
+            queue.take_reneged(),
+            vec![Reneged {
+                value: 'a',
+                arrival: 0,
+                expiry: 2
+            }]
+        );
+    }
+
+    struct Abandonment {
+        queue: crate::QueueId<DeadlineQueue<u32>>,
+        abandoned: crate::Key<Vec<(u32, Duration)>>,
+    }
+
+    impl Component for Abandonment {
+        type Event = ();
+
+        fn process_event(
+            &self,
+            _self_id: ComponentId<()>,
+            _event: &(),
+            scheduler: &mut Scheduler,
+            state: &mut State,
+        ) {
+            let queue = state.queue_mut(self.queue);
+            queue.purge_expired();
+            let reneged = queue.take_reneged();
+            let abandoned = state.get_mut(self.abandoned).unwrap();
+            abandoned.extend(
+                reneged
+                    .into_iter()
+                    .map(|element| (element.value, scheduler.time())),
+            );
+        }
+    }
+
+    #[test]
+    fn test_expiry_event() {
+        let mut simulation = Simulation::default();
+        let queue = simulation.add_queue(DeadlineQueue::new(simulation.scheduler.clock()));
+        let abandoned = simulation.state.insert(Vec::new());
+        let component = simulation.add_component(Abandonment { queue, abandoned });
+        for (value, patience) in [(1, 3), (2, 1), (3, 5)].iter().copied() {
+            simulation
+                .state
+                .queue_mut(queue)
+                .push_with_expiry_event(
+                    (value, secs(patience)),
+                    &mut simulation.scheduler,
+                    component,
+                    (),
+                )
+                .unwrap();
+        }
+        // Element 1 is served before it expires, so its event finds nothing.
+        assert_eq!(simulation.state.recv(queue), Some((1, secs(3))));
+        simulation.execute(crate::Executor::unbound()).unwrap();
+        assert_eq!(
+            simulation.state.get(abandoned).unwrap(),
+            &vec![(2, secs(1)), (3, secs(5))]
+        );
+        assert_eq!(simulation.state.recv(queue), None);
+    }
+
+    /// Pushes an element with unlimited `patience` after time zero, and returns the time
+    /// of its expiry event.
+    fn unlimited_expiry_event<T: SimTime>(start: T, patience: T) -> T {
+        let mut scheduler = Scheduler::<T>::new();
+        let component = ComponentId::<()>::new(0, 0);
+        scheduler.schedule(start, component, ());
+        scheduler.pop();
+        let mut queue = DeadlineQueue::new(scheduler.clock());
+        queue
+            .push_with_expiry_event(('a', patience), &mut scheduler, component, ())
+            .unwrap();
+        assert_eq!(
+            queue.next_expiry(),
+            scheduler.pop().map(|entry| entry.time())
+        );
+        assert_eq!(queue.purge_expired(), 1);
+        scheduler.time()
+    }
+
+    #[test]
+    fn test_unlimited_patience() {
+        assert_eq!(
+            unlimited_expiry_event(secs(1), Duration::MAX),
+            Duration::MAX
+        );
+        assert_eq!(unlimited_expiry_event(1, u64::MAX), u64::MAX);
+    }
+}
@@ -0,0 +1,582 @@
+//! Random variate distributions producing [`Duration`]s, e.g., inter-arrival or service times.
+//!
+//! All distributions implement [`Distribution`], which draws samples from any [`RngCore`],
+//! in particular, from a stream managed by the state (see [`State::rng_stream`](crate::State::rng_stream)).
+//!
+//! Parameters are validated when a distribution is constructed, and an invalid parameter results
+//! in a [`ParameterError`]. Samples that cannot be represented as a [`Duration`] are clamped:
+//! negative and NaN values become zero, and values that are too large become [`Duration::MAX`].
+//!
+//! # Example
+//!
+//! ```
+//! # use simrs::State;
+//! # use std::time::Duration;
+//! use simrs::dist::{Distribution, Exponential};
+//! let mut state = State::with_seed(1);
+//! let interarrival = Exponential::new(Duration::from_secs(2));
+//! let interval: Duration = interarrival.sample(state.rng_stream("arrivals"));
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use rand_core::RngCore;
+
+/// Error returned when constructing a distribution with invalid parameters.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ParameterError {
+    /// Description of the violated requirement.
+    pub message: &'static str,
+}
+
+impl ParameterError {
+    fn new(message: &'static str) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid distribution parameter: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+/// Distribution of durations.
+pub trait Distribution {
+    /// Draws a single duration using `rng` as the source of randomness.
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration;
+}
+
+/// Converts seconds to a duration, mapping negative and NaN values to zero, and values
+/// exceeding the range of [`Duration`] to [`Duration::MAX`].
+fn to_duration(secs: f64) -> Duration {
+    if secs.is_nan() || secs <= 0.0 {
+        Duration::ZERO
+    } else {
+        Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+    }
+}
+
+/// Draws a number uniformly from `[0, 1)`.
+#[allow(clippy::cast_precision_loss)]
+fn uniform(rng: &mut dyn RngCore) -> f64 {
+    (rng.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Draws a number uniformly from `(0, 1]`, which is safe to pass to a logarithm.
+fn open_uniform(rng: &mut dyn RngCore) -> f64 {
+    1.0 - uniform(rng)
+}
+
+/// Draws a number from the standard normal distribution using the polar method.
+fn standard_normal(rng: &mut dyn RngCore) -> f64 {
+    loop {
+        let x = 2.0 * uniform(rng) - 1.0;
+        let y = 2.0 * uniform(rng) - 1.0;
+        let s = x * x + y * y;
+        if s > 0.0 && s < 1.0 {
+            return x * (-2.0 * s.ln() / s).sqrt();
+        }
+    }
+}
+
+/// Draws a number from the gamma distribution with unit scale using the method of
+/// Marsaglia and Tsang.
+fn standard_gamma(shape: f64, rng: &mut dyn RngCore) -> f64 {
+    if shape < 1.0 {
+        return standard_gamma(shape + 1.0, rng) * open_uniform(rng).powf(1.0 / shape);
+    }
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = standard_normal(rng);
+        let v = 1.0 + c * x;
+        if v <= 0.0 {
+            continue;
+        }
+        let v = v * v * v;
+        if open_uniform(rng).ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+/// Uniform distribution over the interval `[low, high)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uniform {
+    low: f64,
+    width: f64,
+}
+
+impl Uniform {
+    /// Constructs a uniform distribution between `low` and `high`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `low` is greater than `high`.
+    pub fn new(low: Duration, high: Duration) -> Result<Self, ParameterError> {
+        if low > high {
+            return Err(ParameterError::new("low must not exceed high"));
+        }
+        Ok(Self {
+            low: low.as_secs_f64(),
+            width: high.as_secs_f64() - low.as_secs_f64(),
+        })
+    }
+}
+
+impl Distribution for Uniform {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        to_duration(self.low + self.width * uniform(rng))
+    }
+}
+
+/// Exponential distribution, typically used for inter-arrival times of a Poisson process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exponential {
+    mean: f64,
+}
+
+impl Exponential {
+    /// Constructs an exponential distribution with the given mean.
+    #[must_use]
+    pub fn new(mean: Duration) -> Self {
+        Self {
+            mean: mean.as_secs_f64(),
+        }
+    }
+
+    /// Constructs an exponential distribution with the given rate, in events per second.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rate is not positive and finite.
+    pub fn with_rate(rate: f64) -> Result<Self, ParameterError> {
+        if !(rate.is_finite() && rate > 0.0) {
+            return Err(ParameterError::new("rate must be positive and finite"));
+        }
+        Ok(Self { mean: 1.0 / rate })
+    }
+}
+
+impl Distribution for Exponential {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        to_duration(-self.mean * open_uniform(rng).ln())
+    }
+}
+
+/// Erlang distribution, i.e., the sum of `k` independent exponentially distributed phases.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Erlang {
+    k: u32,
+    phase_mean: f64,
+}
+
+impl Erlang {
+    /// Constructs an Erlang distribution with `k` phases and the given total `mean`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `k` is zero.
+    pub fn new(k: u32, mean: Duration) -> Result<Self, ParameterError> {
+        if k == 0 {
+            return Err(ParameterError::new("number of phases must be positive"));
+        }
+        Ok(Self {
+            k,
+            phase_mean: mean.as_secs_f64() / f64::from(k),
+        })
+    }
+}
+
+impl Distribution for Erlang {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        let sum: f64 = (0..self.k).map(|_| open_uniform(rng).ln()).sum();
+        to_duration(-self.phase_mean * sum)
+    }
+}
+
+/// Gamma distribution with the given shape and scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gamma {
+    shape: f64,
+    scale: f64,
+}
+
+impl Gamma {
+    /// Constructs a gamma distribution with the given `shape` and `scale`.
+    /// Its mean is `shape * scale`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shape is not positive and finite.
+    pub fn new(shape: f64, scale: Duration) -> Result<Self, ParameterError> {
+        if !(shape.is_finite() && shape > 0.0) {
+            return Err(ParameterError::new("shape must be positive and finite"));
+        }
+        Ok(Self {
+            shape,
+            scale: scale.as_secs_f64(),
+        })
+    }
+}
+
+impl Distribution for Gamma {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        to_duration(self.scale * standard_gamma(self.shape, rng))
+    }
+}
+
+/// Log-normal distribution, i.e., the distribution of `exp(X)` seconds, where `X` is normally
+/// distributed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogNormal {
+    mu: f64,
+    sigma: f64,
+}
+
+impl LogNormal {
+    /// Constructs a log-normal distribution, where `mu` and `sigma` are the mean and standard
+    /// deviation of the underlying normal distribution of the logarithm of seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `mu` is not finite, or `sigma` is not non-negative and finite.
+    pub fn new(mu: f64, sigma: f64) -> Result<Self, ParameterError> {
+        if !mu.is_finite() {
+            return Err(ParameterError::new("mu must be finite"));
+        }
+        if !(sigma.is_finite() && sigma >= 0.0) {
+            return Err(ParameterError::new("sigma must be non-negative and finite"));
+        }
+        Ok(Self { mu, sigma })
+    }
+
+    /// Constructs a log-normal distribution with the given mean and standard deviation
+    /// of the durations themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mean is zero.
+    pub fn from_mean_std(mean: Duration, std_dev: Duration) -> Result<Self, ParameterError> {
+        if mean.is_zero() {
+            return Err(ParameterError::new("mean must be positive"));
+        }
+        let mean = mean.as_secs_f64();
+        let cv = std_dev.as_secs_f64() / mean;
+        let sigma_squared = cv.mul_add(cv, 1.0).ln();
+        Self::new(mean.ln() - sigma_squared / 2.0, sigma_squared.sqrt())
+    }
+}
+
+impl Distribution for LogNormal {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        to_duration(self.sigma.mul_add(standard_normal(rng), self.mu).exp())
+    }
+}
+
+/// Weibull distribution with the given shape and scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weibull {
+    inverse_shape: f64,
+    scale: f64,
+}
+
+impl Weibull {
+    /// Constructs a Weibull distribution with the given `shape` and `scale`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shape is not positive and finite.
+    pub fn new(shape: f64, scale: Duration) -> Result<Self, ParameterError> {
+        if !(shape.is_finite() && shape > 0.0) {
+            return Err(ParameterError::new("shape must be positive and finite"));
+        }
+        Ok(Self {
+            inverse_shape: 1.0 / shape,
+            scale: scale.as_secs_f64(),
+        })
+    }
+}
+
+impl Distribution for Weibull {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        to_duration(self.scale * (-open_uniform(rng).ln()).powf(self.inverse_shape))
+    }
+}
+
+/// Triangular distribution between `min` and `max`, with the peak at `mode`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangular {
+    min: f64,
+    mode: f64,
+    max: f64,
+}
+
+impl Triangular {
+    /// Constructs a triangular distribution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless `min <= mode <= max`.
+    pub fn new(min: Duration, mode: Duration, max: Duration) -> Result<Self, ParameterError> {
+        if !(min <= mode && mode <= max) {
+            return Err(ParameterError::new("mode must be between min and max"));
+        }
+        Ok(Self {
+            min: min.as_secs_f64(),
+            mode: mode.as_secs_f64(),
+            max: max.as_secs_f64(),
+        })
+    }
+}
+
+impl Distribution for Triangular {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        let Self { min, mode, max } = *self;
+        let width = max - min;
+        if width <= 0.0 {
+            return to_duration(min);
+        }
+        let u = uniform(rng);
+        let split = (mode - min) / width;
+        if u < split {
+            to_duration(min + (u * width * (mode - min)).sqrt())
+        } else {
+            to_duration(max - ((1.0 - u) * width * (max - mode)).sqrt())
+        }
+    }
+}
+
+/// Empirical distribution, which draws uniformly from a set of observed durations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Empirical {
+    observations: Vec<Duration>,
+}
+
+impl Empirical {
+    /// Constructs an empirical distribution from the given observations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no observations.
+    pub fn new<I: IntoIterator<Item = Duration>>(observations: I) -> Result<Self, ParameterError> {
+        let observations: Vec<_> = observations.into_iter().collect();
+        if observations.is_empty() {
+            return Err(ParameterError::new("at least one observation is required"));
+        }
+        Ok(Self { observations })
+    }
+}
+
+impl Distribution for Empirical {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        let len = self.observations.len();
+        let idx = (uniform(rng) * len as f64) as usize;
+        self.observations[idx.min(len - 1)]
+    }
+}
+
+/// Discrete distribution over a finite set of durations with the given weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discrete {
+    values: Vec<Duration>,
+    cumulative_weights: Vec<f64>,
+}
+
+impl Discrete {
+    /// Constructs a discrete distribution from `(value, weight)` pairs. The weights are
+    /// relative, i.e., they do not have to sum to one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any weight is negative or not finite, or if all weights are zero.
+    pub fn new<I>(values: I) -> Result<Self, ParameterError>
+    where
+        I: IntoIterator<Item = (Duration, f64)>,
+    {
+        let mut total = 0.0;
+        let mut cumulative_weights = Vec::new();
+        let mut result = Vec::new();
+        for (value, weight) in values {
+            if !(weight.is_finite() && weight >= 0.0) {
+                return Err(ParameterError::new(
+                    "weights must be non-negative and finite",
+                ));
+            }
+            total += weight;
+            cumulative_weights.push(total);
+            result.push(value);
+        }
+        if !(total.is_finite() && total > 0.0) {
+            return Err(ParameterError::new(
+                "total weight must be positive and finite",
+            ));
+        }
+        Ok(Self {
+            values: result,
+            cumulative_weights,
+        })
+    }
+}
+
+impl Distribution for Discrete {
+    fn sample(&self, rng: &mut dyn RngCore) -> Duration {
+        let total = self.cumulative_weights[self.cumulative_weights.len() - 1];
+        let target = uniform(rng) * total;
+        let idx = self.cumulative_weights.partition_point(|&w| w <= target);
+        self.values[idx.min(self.values.len() - 1)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::State;
+
+    const SAMPLES: usize = 20_000;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn mean_and_variance<D: Distribution>(dist: &D) -> (f64, f64) {
+        let mut state = State::with_seed(17);
+        let rng = state.rng_stream("test");
+        let samples: Vec<f64> = (0..SAMPLES)
+            .map(|_| dist.sample(rng).as_secs_f64())
+            .collect();
+        let mean = samples.iter().sum::<f64>() / SAMPLES as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / SAMPLES as f64;
+        (mean, variance)
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs().max(1.0),
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn test_to_duration() {
+        assert_eq!(to_duration(f64::NAN), Duration::ZERO);
+        assert_eq!(to_duration(-1.0), Duration::ZERO);
+        assert_eq!(to_duration(f64::NEG_INFINITY), Duration::ZERO);
+        assert_eq!(to_duration(f64::INFINITY), Duration::MAX);
+        assert_eq!(to_duration(1e30), Duration::MAX);
+        assert_eq!(to_duration(1.5), secs(1.5));
+    }
+
+    #[test]
+    fn test_moments() {
+        let (mean, variance) = mean_and_variance(&Uniform::new(secs(1.0), secs(3.0)).unwrap());
+        assert_close(mean, 2.0, 0.02);
+        assert_close(variance, 1.0 / 3.0, 0.05);
+
+        let (mean, variance) = mean_and_variance(&Exponential::new(secs(2.0)));
+        assert_close(mean, 2.0, 0.03);
+        assert_close(variance, 4.0, 0.06);
+        let (mean, _) = mean_and_variance(&Exponential::with_rate(4.0).unwrap());
+        assert_close(mean, 0.25, 0.03);
+
+        let (mean, variance) = mean_and_variance(&Erlang::new(3, secs(3.0)).unwrap());
+        assert_close(mean, 3.0, 0.03);
+        assert_close(variance, 3.0, 0.06);
+
+        for &shape in &[0.5, 1.0, 2.5] {
+            let (mean, variance) = mean_and_variance(&Gamma::new(shape, secs(2.0)).unwrap());
+            assert_close(mean, shape * 2.0, 0.04);
+            assert_close(variance, shape * 4.0, 0.08);
+        }
+
+        let (mean, variance) =
+            mean_and_variance(&LogNormal::from_mean_std(secs(2.0), secs(1.0)).unwrap());
+        assert_close(mean, 2.0, 0.03);
+        assert_close(variance, 1.0, 0.08);
+
+        // With shape 1, the Weibull distribution is exponential.
+        let (mean, variance) = mean_and_variance(&Weibull::new(1.0, secs(2.0)).unwrap());
+        assert_close(mean, 2.0, 0.03);
+        assert_close(variance, 4.0, 0.06);
+
+        let (mean, variance) =
+            mean_and_variance(&Triangular::new(secs(0.0), secs(1.0), secs(5.0)).unwrap());
+        assert_close(mean, 2.0, 0.02);
+        assert_close(variance, (1.0 + 25.0 - 5.0) / 18.0, 0.05);
+    }
+
+    #[test]
+    fn test_finite_supports() {
+        let mut state = State::with_seed(3);
+        let rng = state.rng_stream("test");
+
+        let empirical = Empirical::new(vec![secs(1.0), secs(2.0), secs(4.0)]).unwrap();
+        let mut counts = [0; 3];
+        for _ in 0..SAMPLES {
+            match empirical.sample(rng).as_secs() {
+                1 => counts[0] += 1,
+                2 => counts[1] += 1,
+                4 => counts[2] += 1,
+                other => panic!("unexpected sample {}", other),
+            }
+        }
+        assert!(counts.iter().all(|&c| c > SAMPLES / 4));
+
+        let discrete =
+            Discrete::new(vec![(secs(1.0), 1.0), (secs(2.0), 0.0), (secs(3.0), 3.0)]).unwrap();
+        let threes = (0..SAMPLES)
+            .map(|_| discrete.sample(rng))
+            .inspect(|&d| assert_ne!(d, secs(2.0)))
+            .filter(|&d| d == secs(3.0))
+            .count();
+        assert!(threes > SAMPLES * 7 / 10 && threes < SAMPLES * 8 / 10);
+
+        let triangular = Triangular::new(secs(1.0), secs(1.0), secs(1.0)).unwrap();
+        assert_eq!(triangular.sample(rng), secs(1.0));
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(Uniform::new(secs(2.0), secs(1.0)).is_err());
+        assert!(Exponential::with_rate(0.0).is_err());
+        assert!(Exponential::with_rate(f64::NAN).is_err());
+        assert!(Erlang::new(0, secs(1.0)).is_err());
+        assert!(Gamma::new(-1.0, secs(1.0)).is_err());
+        assert!(LogNormal::new(f64::NAN, 1.0).is_err());
+        assert!(LogNormal::new(0.0, -1.0).is_err());
+        assert!(LogNormal::from_mean_std(Duration::ZERO, secs(1.0)).is_err());
+        assert!(Weibull::new(f64::INFINITY, secs(1.0)).is_err());
+        assert!(Triangular::new(secs(1.0), secs(3.0), secs(2.0)).is_err());
+        assert!(Empirical::new(Vec::new()).is_err());
+        assert!(Discrete::new(vec![(secs(1.0), -1.0)]).is_err());
+        assert!(Discrete::new(vec![(secs(1.0), 0.0)]).is_err());
+        assert_eq!(
+            Erlang::new(0, secs(1.0)).unwrap_err().to_string(),
+            "invalid distribution parameter: number of phases must be positive"
+        );
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let dist = Gamma::new(0.7, secs(1.0)).unwrap();
+        let draw = |seed| {
+            let mut state = State::with_seed(seed);
+            let rng = state.rng_stream("test");
+            (0..10).map(|_| dist.sample(rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(5), draw(5));
+        assert_ne!(draw(5), draw(6));
+    }
+}
@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::sync::ErrorBox;
+
+/// Error returned by the fallible simulation operations, such as [`Simulation::try_step`](crate::Simulation::try_step).
+///
+/// These errors typically signal a programming error, such as an ID issued by another simulation,
+/// but unlike panics, they can be reported without aborting, e.g., a long batch of runs.
+#[derive(Debug)]
+pub enum SimError {
+    /// No component with the given index is registered.
+    MissingComponent {
+        /// Index of the component.
+        component: usize,
+    },
+    /// The component with the given index cannot process the event because the event type
+    /// does not match the type declared by the component.
+    EventTypeMismatch {
+        /// Index of the component.
+        component: usize,
+    },
+    /// The component ID was issued by another simulation.
+    ForeignComponent {
+        /// Index of the component.
+        component: usize,
+    },
+    /// No queue with the given ID exists in the state.
+    MissingQueue {
+        /// ID of the queue.
+        queue: usize,
+    },
+    /// The queue with the given ID exists but has a different type than requested.
+    QueueTypeMismatch {
+        /// ID of the queue.
+        queue: usize,
+    },
+    /// The queue ID was issued by another simulation.
+    ForeignQueue {
+        /// ID of the queue.
+        queue: usize,
+    },
+    /// A [`TryComponent`](crate::TryComponent) returned an error when processing an event.
+    Component {
+        /// Index of the component.
+        component: usize,
+        /// The error returned by the component.
+        error: ErrorBox,
+    },
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingComponent { component } => {
+                write!(f, "component {component} does not exist")
+            }
+            Self::EventTypeMismatch { component } => {
+                write!(f, "event type does not match component {component}")
+            }
+            Self::ForeignComponent { component } => {
+                write!(f, "component {component} belongs to another simulation")
+            }
+            Self::ForeignQueue { queue } => {
+                write!(f, "queue {queue} belongs to another simulation")
+            }
+            Self::MissingQueue { queue } => write!(f, "queue {queue} does not exist"),
+            Self::QueueTypeMismatch { queue } => {
+                write!(f, "queue {queue} has a different type than requested")
+            }
+            Self::Component { component, error } => {
+                write!(f, "component {component} failed: {error}")
+            }
+        }
+    }
+}
+
+impl Error for SimError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Component { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when the execution of a simulation is halted,
+/// see [`Execute`](crate::Execute).
+///
+/// Along with the cause, it records the simulation time at which the error occurred
+/// and the index of the offending component.
+#[derive(Debug)]
+pub struct ExecuteError<T = Duration> {
+    /// Simulation time at which the failed event occurred.
+    pub time: T,
+    /// Index of the component the failed event was scheduled for.
+    pub component: usize,
+    /// The cause of the failure.
+    pub error: SimError,
+}
+
+impl<T: fmt::Debug> fmt::Display for ExecuteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "simulation halted at {:?}: {}", self.time, self.error)
+    }
+}
+
+impl<T: fmt::Debug> Error for ExecuteError<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::BinaryHeap;
+use std::time::Duration;
+
+use crate::{EventEntry, MaybeSend, SimTime};
+
+/// Future event list used by the [`Scheduler`](crate::Scheduler) to store upcoming events.
+///
+/// The queue must always return the _greatest_ entry according to the [`Ord`] implementation
+/// of [`EventEntry`], which is the event that is the next to occur.
+///
+/// The following implementations are provided:
+/// - [`BinaryHeapQueue`]: the default; `O(log n)` push and pop with a good constant factor.
+/// - [`CalendarQueue`]: `O(1)` amortized operations when the event times are evenly spread,
+///   at the expense of higher memory usage. Best suited for large models with millions of
+///   pending events.
+/// - [`PairingHeap`]: `O(1)` push and `O(log n)` amortized pop, which can be beneficial when
+///   many events are scheduled but only some are processed, e.g., due to cancellations.
+///
+/// # Trade-offs
+///
+/// The benchmarks in `benches/event_queue.rs` measure the hold model, where every operation
+/// pops the next event and schedules a new one, keeping the number of pending events constant:
+/// - With a moderate number of pending events, [`BinaryHeapQueue`] is a good default:
+///   it is competitive with [`CalendarQueue`] and has the lower memory overhead.
+/// - With a large number of pending events, [`CalendarQueue`] tends to be faster.
+///   Its advantage shrinks when the time increments are very unevenly distributed,
+///   because the bucket width is estimated from the separation of the earliest events.
+/// - [`PairingHeap`] is typically slower on this workload, since every pop restructures
+///   the heap. Prefer it only when most scheduled events are cancelled before they would
+///   be popped.
+///
+/// The actual numbers depend on the machine and the model, so run
+/// `cargo bench --bench event_queue` to compare the queues for your workload.
+pub trait EventQueue<T: SimTime = Duration>: MaybeSend + 'static {
+    /// Inserts a new entry to the queue.
+    fn push(&mut self, entry: EventEntry<T>);
+
+    /// Removes and returns the next entry or `None` if the queue is empty.
+    fn pop(&mut self) -> Option<EventEntry<T>>;
+
+    /// Returns a reference to the next entry or `None` if the queue is empty.
+    fn peek(&mut self) -> Option<&EventEntry<T>>;
+
+    /// Retains only the entries for which `predicate` returns `true`.
+    fn retain(&mut self, predicate: &mut dyn FnMut(&EventEntry<T>) -> bool);
+
+    /// Returns an iterator over all entries in an arbitrary order.
+    fn iter(&self) -> Box<dyn Iterator<Item = &EventEntry<T>> + '_>;
+
+    /// Returns the number of entries in the queue.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no entries in the queue.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Event queue implemented with the standard [`BinaryHeap`].
+pub struct BinaryHeapQueue<T = Duration> {
+    inner: BinaryHeap<EventEntry<T>>,
+}
+
+impl<T: SimTime> Default for BinaryHeapQueue<T> {
+    fn default() -> Self {
+        Self {
+            inner: BinaryHeap::default(),
+        }
+    }
+}
+
+impl<T: SimTime> EventQueue<T> for BinaryHeapQueue<T> {
+    fn push(&mut self, entry: EventEntry<T>) {
+        self.inner.push(entry);
+    }
+
+    fn pop(&mut self) -> Option<EventEntry<T>> {
+        self.inner.pop()
+    }
+
+    fn peek(&mut self) -> Option<&EventEntry<T>> {
+        self.inner.peek()
+    }
+
+    fn retain(&mut self, predicate: &mut dyn FnMut(&EventEntry<T>) -> bool) {
+        self.inner.retain(predicate);
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &EventEntry<T>> + '_> {
+        Box::new(self.inner.iter())
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Calendar queue, as described by R. Brown in _Calendar Queues: A Fast O(1) Priority Queue
+/// Implementation for the Simulation Event Set Problem_ (1988).
+///
+/// Events are distributed among buckets ("days") of a fixed width based on their time
+/// (see [`SimTime::to_f64`]). The number of buckets and their width are adjusted
+/// as the queue grows and shrinks.
+pub struct CalendarQueue<T = Duration> {
+    /// Each bucket is sorted in the ascending order, so that the next event is at the end.
+    buckets: Vec<Vec<EventEntry<T>>>,
+    width: f64,
+    /// The number of the current day since the beginning of time; `current % buckets.len()`
+    /// is the index of the current bucket.
+    current: u64,
+    len: usize,
+}
+
+const CALENDAR_MIN_BUCKETS: usize = 2;
+const CALENDAR_WIDTH_SAMPLE: usize = 25;
+
+impl<T: SimTime> Default for CalendarQueue<T> {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl<T: SimTime> CalendarQueue<T> {
+    /// Creates a new calendar queue with the given initial bucket width, expressed in the
+    /// units of [`SimTime::to_f64`]. The width will be adjusted when the queue is resized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width is not a positive finite number.
+    #[must_use]
+    pub fn new(width: f64) -> Self {
+        assert!(
+            width.is_finite() && width > 0.0,
+            "Bucket width must be positive and finite."
+        );
+        Self {
+            buckets: (0..CALENDAR_MIN_BUCKETS).map(|_| Vec::new()).collect(),
+            width,
+            current: 0,
+            len: 0,
+        }
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn day(&self, time: T) -> u64 {
+        let day = (time.to_f64() / self.width).floor();
+        if day.is_nan() {
+            u64::MAX
+        } else {
+            day as u64
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn bucket(&self, day: u64) -> usize {
+        (day % self.buckets.len() as u64) as usize
+    }
+
+    fn insert(&mut self, entry: EventEntry<T>) {
+        let day = self.day(entry.time());
+        let bucket = self.bucket(day);
+        let bucket = &mut self.buckets[bucket];
+        let position = bucket.partition_point(|e| e < &entry);
+        bucket.insert(position, entry);
+    }
+
+    /// Moves the current day to the one containing the next event and returns the bucket index,
+    /// or `None` if the queue is empty.
+    fn find_next(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        for _ in 0..self.buckets.len() {
+            let bucket = self.bucket(self.current);
+            if let Some(entry) = self.buckets[bucket].last() {
+                if self.day(entry.time()) <= self.current {
+                    return Some(bucket);
+                }
+            }
+            self.current += 1;
+        }
+        // No event in an entire year: find the earliest event directly.
+        let day = self
+            .buckets
+            .iter()
+            .filter_map(|bucket| bucket.last())
+            .max()
+            .map(|entry| self.day(entry.time()))
+            .expect("Queue is not empty.");
+        self.current = day;
+        Some(self.bucket(day))
+    }
+
+    fn resize(&mut self, buckets: usize) {
+        let mut entries: Vec<_> = self.buckets.iter_mut().flat_map(std::mem::take).collect();
+        self.width = Self::estimate_width(&mut entries).unwrap_or(self.width);
+        self.buckets = (0..buckets).map(|_| Vec::new()).collect();
+        self.current = entries
+            .iter()
+            .max()
+            .map_or(self.current, |entry| self.day(entry.time()));
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+
+    /// Estimates the bucket width as three times the average separation of the earliest events.
+    #[allow(clippy::cast_precision_loss)]
+    fn estimate_width(entries: &mut [EventEntry<T>]) -> Option<f64> {
+        if entries.len() < 2 {
+            return None;
+        }
+        let sample = CALENDAR_WIDTH_SAMPLE.min(entries.len());
+        let (earliest, _, _) = entries.select_nth_unstable_by(sample - 1, |a, b| b.cmp(a));
+        let mut times: Vec<f64> = earliest.iter().map(|e| e.time().to_f64()).collect();
+        times.push(entries[sample - 1].time().to_f64());
+        times.sort_by(f64::total_cmp);
+        let width = 3.0 * (times[times.len() - 1] - times[0]) / (times.len() - 1) as f64;
+        if width.is_finite() && width > 0.0 {
+            Some(width)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: SimTime> EventQueue<T> for CalendarQueue<T> {
+    fn push(&mut self, entry: EventEntry<T>) {
+        let day = self.day(entry.time());
+        if day < self.current {
+            self.current = day;
+        }
+        self.insert(entry);
+        self.len += 1;
+        if self.len > 2 * self.buckets.len() {
+            self.resize(2 * self.buckets.len());
+        }
+    }
+
+    fn pop(&mut self) -> Option<EventEntry<T>> {
+        let bucket = self.find_next()?;
+        let entry = self.buckets[bucket].pop();
+        self.len -= 1;
+        if self.buckets.len() > CALENDAR_MIN_BUCKETS && self.len < self.buckets.len() / 2 {
+            self.resize(self.buckets.len() / 2);
+        }
+        entry
+    }
+
+    fn peek(&mut self) -> Option<&EventEntry<T>> {
+        let bucket = self.find_next()?;
+        self.buckets[bucket].last()
+    }
+
+    fn retain(&mut self, predicate: &mut dyn FnMut(&EventEntry<T>) -> bool) {
+        for bucket in &mut self.buckets {
+            bucket.retain(|entry| predicate(entry));
+        }
+        self.len = self.buckets.iter().map(Vec::len).sum();
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &EventEntry<T>> + '_> {
+        Box::new(self.buckets.iter().flatten())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+struct PairingNode<T> {
+    entry: EventEntry<T>,
+    children: Vec<PairingNode<T>>,
+}
+
+/// Pairing heap implementation of [`EventQueue`].
+pub struct PairingHeap<T = Duration> {
+    root: Option<PairingNode<T>>,
+    len: usize,
+}
+
+impl<T: SimTime> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self { root: None, len: 0 }
+    }
+}
+
+impl<T: SimTime> PairingHeap<T> {
+    fn meld(mut a: PairingNode<T>, mut b: PairingNode<T>) -> PairingNode<T> {
+        if a.entry < b.entry {
+            std::mem::swap(&mut a, &mut b);
+        }
+        a.children.push(b);
+        a
+    }
+
+    /// Standard two-pass pairing of the subtrees left after removing the root.
+    /// The most recently added children are at the end, so they are paired first.
+    fn merge_pairs(children: Vec<PairingNode<T>>) -> Option<PairingNode<T>> {
+        let mut paired = Vec::with_capacity(children.len() / 2 + 1);
+        let mut children = children.into_iter().rev();
+        while let Some(first) = children.next() {
+            match children.next() {
+                Some(second) => paired.push(Self::meld(first, second)),
+                None => paired.push(first),
+            }
+        }
+        let mut root = paired.pop()?;
+        while let Some(node) = paired.pop() {
+            root = Self::meld(root, node);
+        }
+        Some(root)
+    }
+
+    /// Removes all entries from the heap without recursion, which could overflow the stack
+    /// for deep trees.
+    fn take_entries(&mut self) -> Vec<EventEntry<T>> {
+        let mut entries = Vec::with_capacity(self.len);
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            entries.push(node.entry);
+            stack.extend(node.children);
+        }
+        self.len = 0;
+        entries
+    }
+}
+
+impl<T> Drop for PairingHeap<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+impl<T: SimTime> EventQueue<T> for PairingHeap<T> {
+    fn push(&mut self, entry: EventEntry<T>) {
+        let node = PairingNode {
+            entry,
+            children: Vec::new(),
+        };
+        self.root = Some(match self.root.take() {
+            Some(root) => Self::meld(root, node),
+            None => node,
+        });
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<EventEntry<T>> {
+        let root = self.root.take()?;
+        self.root = Self::merge_pairs(root.children);
+        self.len -= 1;
+        Some(root.entry)
+    }
+
+    fn peek(&mut self) -> Option<&EventEntry<T>> {
+        self.root.as_ref().map(|root| &root.entry)
+    }
+
+    fn retain(&mut self, predicate: &mut dyn FnMut(&EventEntry<T>) -> bool) {
+        for entry in self.take_entries() {
+            if predicate(&entry) {
+                self.push(entry);
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &EventEntry<T>> + '_> {
+        let mut stack: Vec<_> = self.root.iter().collect();
+        Box::new(std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(&node.children);
+            Some(&node.entry)
+        }))
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ComponentId;
+
+    /// Simple deterministic pseudo-random generator for the tests.
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    fn entry(time: u64, sequence: usize) -> EventEntry<u64> {
+        let mut entry = EventEntry::new(time, ComponentId::<usize>::new(0, 0), sequence);
+        entry.sequence = sequence;
+        entry
+    }
+
+    fn check_hold_model<Q: EventQueue<u64>>(mut queue: Q) {
+        let mut reference = BinaryHeapQueue::default();
+        let mut state = 7;
+        let mut sequence = 0;
+        let mut push = |queue: &mut Q, reference: &mut BinaryHeapQueue<u64>, time| {
+            sequence += 1;
+            queue.push(entry(time, sequence));
+            reference.push(entry(time, sequence));
+        };
+        for _ in 0..1000 {
+            let time = xorshift(&mut state) % 100;
+            push(&mut queue, &mut reference, time);
+        }
+        for step in 0..5000 {
+            assert_eq!(queue.len(), reference.len());
+            let expected = reference.pop().unwrap();
+            assert!(queue.peek() == Some(&expected));
+            let actual = queue.pop().unwrap();
+            assert!(actual == expected);
+            // Grow, shrink, and hold steady, with some simultaneous events.
+            let pushes = match step / 1000 {
+                0 | 4 => 2,
+                2 => 0,
+                _ => 1,
+            };
+            for _ in 0..pushes {
+                let time = actual.time() + xorshift(&mut state) % 50;
+                push(&mut queue, &mut reference, time);
+            }
+        }
+        queue.retain(&mut |entry| entry.sequence % 2 == 0);
+        reference.retain(&mut |entry| entry.sequence % 2 == 0);
+        assert_eq!(queue.len(), reference.len());
+        let sorted_sequences = |queue: &dyn EventQueue<u64>| {
+            let mut sequences: Vec<_> = queue.iter().map(|entry| entry.sequence).collect();
+            sequences.sort_unstable();
+            sequences
+        };
+        assert_eq!(sorted_sequences(&queue), sorted_sequences(&reference));
+        while let Some(expected) = reference.pop() {
+            assert!(queue.pop() == Some(expected));
+        }
+        assert!(queue.is_empty());
+        assert!(queue.pop().is_none());
+        assert!(queue.peek().is_none());
+    }
+
+    #[test]
+    fn test_binary_heap_queue() {
+        check_hold_model(BinaryHeapQueue::default());
+    }
+
+    #[test]
+    fn test_calendar_queue() {
+        check_hold_model(CalendarQueue::default());
+        check_hold_model(CalendarQueue::new(0.001));
+        check_hold_model(CalendarQueue::new(1000.0));
+    }
+
+    #[test]
+    fn test_calendar_queue_push_before_current() {
+        let mut queue = CalendarQueue::new(1.0);
+        queue.push(entry(10, 0));
+        assert_eq!(queue.peek().unwrap().time(), 10);
+        queue.push(entry(3, 1));
+        assert_eq!(queue.pop().unwrap().time(), 3);
+        assert_eq!(queue.pop().unwrap().time(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bucket width must be positive and finite.")]
+    fn test_calendar_queue_invalid_width() {
+        let _ = CalendarQueue::<u64>::new(0.0);
+    }
+
+    #[test]
+    fn test_pairing_heap() {
+        check_hold_model(PairingHeap::default());
+    }
+
+    #[test]
+    fn test_pairing_heap_deep_drop() {
+        let mut heap = PairingHeap::default();
+        for time in (0..1_000_000).rev() {
+            heap.push(entry(time, 0));
+        }
+        assert_eq!(heap.len(), 1_000_000);
+    }
+}
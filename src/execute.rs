@@ -1,16 +1,21 @@
-use crate::Simulation;
+use crate::{BinaryHeapQueue, EventQueue, ExecuteError, SimTime, Simulation};
+use std::marker::PhantomData;
 use std::time::Duration;
 
 /// Simulation execution trait.
-pub trait Execute {
+pub trait Execute<T: SimTime = Duration, Q: EventQueue<T> = BinaryHeapQueue<T>> {
     /// Executes the simulation until some stopping condition is reached.
     /// The condition is implementation-specific.
-    fn execute(self, sim: &mut Simulation);
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if processing an event fails. The execution must stop at the first error.
+    fn execute(self, sim: &mut Simulation<T, Q>) -> Result<(), ExecuteError<T>>;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum EndCondition {
-    Time(Duration),
+enum EndCondition<T> {
+    Time(T),
     EmptyQueue,
     Steps(usize),
 }
@@ -19,11 +24,11 @@ enum EndCondition {
 ///
 /// See the crate level documentation for examples.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Executor {
-    end_condition: EndCondition,
+pub struct Executor<T = Duration> {
+    end_condition: EndCondition<T>,
 }
 
-impl Executor {
+impl<T: SimTime> Executor<T> {
     /// Simulation will end only once there is no available events in the queue.
     #[must_use]
     pub fn unbound() -> Self {
@@ -35,7 +40,7 @@ impl Executor {
     /// Simulation will be run no longer than the given time.
     /// It may terminate early if no events are available.
     #[must_use]
-    pub fn timed(time: Duration) -> Self {
+    pub fn timed(time: T) -> Self {
         Self {
             end_condition: EndCondition::Time(time),
         }
@@ -51,50 +56,61 @@ impl Executor {
 
     /// Registers a side effect that is called _after_ each simulation step.
     #[must_use]
-    pub fn side_effect<F>(self, func: F) -> ExecutorWithSideEffect<F>
+    pub fn side_effect<F, Q>(self, func: F) -> ExecutorWithSideEffect<F, T, Q>
     where
-        F: Fn(&Simulation),
+        Q: EventQueue<T>,
+        F: Fn(&Simulation<T, Q>),
     {
         ExecutorWithSideEffect {
             end_condition: self.end_condition,
             side_effect: func,
+            _marker: PhantomData,
         }
     }
 }
 
-impl Execute for Executor {
-    fn execute(self, sim: &mut Simulation) {
-        run_with(sim, self.end_condition, |_| {});
+impl<T: SimTime, Q: EventQueue<T>> Execute<T, Q> for Executor<T> {
+    fn execute(self, sim: &mut Simulation<T, Q>) -> Result<(), ExecuteError<T>> {
+        run_with(sim, self.end_condition, |_| {})
     }
 }
 
-pub struct ExecutorWithSideEffect<F>
+pub struct ExecutorWithSideEffect<F, T = Duration, Q = BinaryHeapQueue<T>>
 where
-    F: Fn(&Simulation),
+    F: Fn(&Simulation<T, Q>),
 {
-    end_condition: EndCondition,
+    end_condition: EndCondition<T>,
     side_effect: F,
+    _marker: PhantomData<fn(&Simulation<T, Q>)>,
 }
 
-impl<F> Execute for ExecutorWithSideEffect<F>
+impl<F, T, Q> Execute<T, Q> for ExecutorWithSideEffect<F, T, Q>
 where
-    F: Fn(&Simulation),
+    T: SimTime,
+    Q: EventQueue<T>,
+    F: Fn(&Simulation<T, Q>),
 {
-    fn execute(self, sim: &mut Simulation) {
-        run_with(sim, self.end_condition, self.side_effect);
+    fn execute(self, sim: &mut Simulation<T, Q>) -> Result<(), ExecuteError<T>> {
+        run_with(sim, self.end_condition, self.side_effect)
     }
 }
 
-fn run_with<F>(sim: &mut Simulation, end_condition: EndCondition, side_effect: F)
+fn run_with<T, Q, F>(
+    sim: &mut Simulation<T, Q>,
+    end_condition: EndCondition<T>,
+    side_effect: F,
+) -> Result<(), ExecuteError<T>>
 where
-    F: Fn(&Simulation),
+    T: SimTime,
+    Q: EventQueue<T>,
+    F: Fn(&Simulation<T, Q>),
 {
-    let step_fn = |sim: &mut Simulation| {
-        let result = sim.step();
+    let step_fn = |sim: &mut Simulation<T, Q>| {
+        let result = sim.step_or_halt()?;
         if result {
             side_effect(sim);
         }
-        result
+        Ok(result)
     };
     match end_condition {
         EndCondition::Time(time) => execute_until(sim, time, step_fn),
@@ -103,31 +119,48 @@ where
     }
 }
 
-fn execute_until_empty<F>(sim: &mut Simulation, step: F)
+fn execute_until_empty<T, Q, F>(sim: &mut Simulation<T, Q>, step: F) -> Result<(), ExecuteError<T>>
 where
-    F: Fn(&mut Simulation) -> bool,
+    T: SimTime,
+    Q: EventQueue<T>,
+    F: Fn(&mut Simulation<T, Q>) -> Result<bool, ExecuteError<T>>,
 {
-    while step(sim) {}
+    while step(sim)? {}
+    Ok(())
 }
 
-fn execute_until<F>(sim: &mut Simulation, time: Duration, step: F)
+fn execute_until<T, Q, F>(
+    sim: &mut Simulation<T, Q>,
+    time: T,
+    step: F,
+) -> Result<(), ExecuteError<T>>
 where
-    F: Fn(&mut Simulation) -> bool,
+    T: SimTime,
+    Q: EventQueue<T>,
+    F: Fn(&mut Simulation<T, Q>) -> Result<bool, ExecuteError<T>>,
 {
-    while sim.scheduler.peek().map_or(false, |e| e.time() <= time) {
-        step(sim);
+    while sim.scheduler.peek().is_some_and(|e| e.time() <= time) {
+        step(sim)?;
     }
+    Ok(())
 }
 
-fn execute_steps<F>(sim: &mut Simulation, steps: usize, step: F)
+fn execute_steps<T, Q, F>(
+    sim: &mut Simulation<T, Q>,
+    steps: usize,
+    step: F,
+) -> Result<(), ExecuteError<T>>
 where
-    F: Fn(&mut Simulation) -> bool,
+    T: SimTime,
+    Q: EventQueue<T>,
+    F: Fn(&mut Simulation<T, Q>) -> Result<bool, ExecuteError<T>>,
 {
     for _ in 0..steps {
-        if !step(sim) {
+        if !step(sim)? {
             break;
         }
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -163,7 +196,7 @@ mod test {
     #[test]
     fn test_create_executor() {
         assert_eq!(
-            Executor::unbound(),
+            Executor::<Duration>::unbound(),
             Executor {
                 end_condition: EndCondition::EmptyQueue
             }
@@ -175,13 +208,13 @@ mod test {
             }
         );
         assert_eq!(
-            Executor::steps(7),
+            Executor::<Duration>::steps(7),
             Executor {
                 end_condition: EndCondition::Steps(7)
             }
         );
         // Bonus: satisfy codecov on derive
-        assert_eq!(&format!("{:?}", TestEvent), "TestEvent");
+        assert_eq!(&format!("{TestEvent:?}"), "TestEvent");
     }
 
     #[test]
@@ -192,7 +225,7 @@ mod test {
             counter: counter_key,
         });
         sim.schedule(Duration::default(), component, TestEvent);
-        Executor::steps(10).execute(&mut sim);
+        Executor::steps(10).execute(&mut sim).unwrap();
         assert_eq!(sim.state.get(counter_key), Some(&10));
     }
 
@@ -205,7 +238,7 @@ mod test {
         });
         sim.schedule(Duration::default(), component, TestEvent);
         // After 10 steps there are no events, so it will not execute all 100
-        Executor::steps(100).execute(&mut sim);
+        Executor::steps(100).execute(&mut sim).unwrap();
         assert_eq!(sim.state.get(counter_key), Some(&10));
     }
 
@@ -217,7 +250,9 @@ mod test {
             counter: counter_key,
         });
         sim.schedule(Duration::default(), component, TestEvent);
-        Executor::timed(Duration::from_secs(6)).execute(&mut sim);
+        Executor::timed(Duration::from_secs(6))
+            .execute(&mut sim)
+            .unwrap();
         assert_eq!(sim.state.get(counter_key), Some(&4));
         assert_eq!(sim.scheduler.clock().time(), Duration::from_secs(6));
     }
@@ -230,8 +265,249 @@ mod test {
             counter: counter_key,
         });
         sim.schedule(Duration::default(), component, TestEvent);
-        Executor::timed(Duration::from_secs(5)).execute(&mut sim);
+        Executor::timed(Duration::from_secs(5))
+            .execute(&mut sim)
+            .unwrap();
         assert_eq!(sim.state.get(counter_key), Some(&3));
         assert_eq!(sim.scheduler.clock().time(), Duration::from_secs(4));
     }
+
+    struct TickComponent {
+        counter: crate::Key<usize>,
+    }
+
+    impl Component<u64> for TickComponent {
+        type Event = TestEvent;
+
+        fn process_event(
+            &self,
+            self_id: crate::ComponentId<Self::Event>,
+            _event: &Self::Event,
+            scheduler: &mut crate::Scheduler<u64>,
+            state: &mut crate::State,
+        ) {
+            *state.get_mut(self.counter).unwrap() += 1;
+            scheduler.schedule(3, self_id, TestEvent);
+        }
+    }
+
+    #[test]
+    fn test_timed_ticks() {
+        let mut sim = Simulation::<u64>::new();
+        let counter_key = sim.state.insert(0_usize);
+        let component = sim.add_component(TickComponent {
+            counter: counter_key,
+        });
+        sim.schedule(1, component, TestEvent);
+        sim.execute(Executor::timed(10)).unwrap();
+        assert_eq!(sim.state.get(counter_key), Some(&4));
+        assert_eq!(sim.scheduler.time(), 10);
+    }
+
+    struct StartStopComponent {
+        counter: crate::Key<usize>,
+        stopped_at: crate::Key<u64>,
+    }
+
+    impl Component<u64> for StartStopComponent {
+        type Event = TestEvent;
+
+        fn process_event(
+            &self,
+            _self_id: crate::ComponentId<Self::Event>,
+            _event: &Self::Event,
+            _scheduler: &mut crate::Scheduler<u64>,
+            state: &mut crate::State,
+        ) {
+            *state.get_mut(self.counter).unwrap() += 1;
+        }
+
+        fn on_start(
+            &self,
+            self_id: crate::ComponentId<Self::Event>,
+            scheduler: &mut crate::Scheduler<u64>,
+            _state: &mut crate::State,
+        ) {
+            scheduler.schedule(2, self_id, TestEvent);
+            scheduler.schedule(5, self_id, TestEvent);
+        }
+
+        fn on_stop(
+            &self,
+            _self_id: crate::ComponentId<Self::Event>,
+            scheduler: &mut crate::Scheduler<u64>,
+            state: &mut crate::State,
+        ) {
+            *state.get_mut(self.stopped_at).unwrap() = scheduler.time();
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_hooks() {
+        let mut sim = Simulation::<u64>::new();
+        let counter = sim.state.insert(0_usize);
+        let stopped_at = sim.state.insert(0_u64);
+        let _ = sim.add_component(StartStopComponent {
+            counter,
+            stopped_at,
+        });
+        sim.execute(Executor::unbound()).unwrap();
+        assert_eq!(sim.state.get(counter), Some(&2));
+        assert_eq!(sim.state.get(stopped_at), Some(&5));
+        assert!(sim.is_stopped());
+    }
+
+    #[test]
+    fn test_stop_at_executor_bound() {
+        let mut sim = Simulation::<u64>::new();
+        let counter = sim.state.insert(0_usize);
+        let stopped_at = sim.state.insert(0_u64);
+        let _ = sim.add_component(StartStopComponent {
+            counter,
+            stopped_at,
+        });
+        sim.execute(Executor::timed(3)).unwrap();
+        assert_eq!(sim.state.get(counter), Some(&1));
+        // The clock stays at the last processed event.
+        assert_eq!(sim.state.get(stopped_at), Some(&2));
+        assert!(sim.is_stopped());
+        // Further executions continue the simulation without calling the hooks again.
+        sim.execute(Executor::unbound()).unwrap();
+        assert_eq!(sim.state.get(counter), Some(&2));
+        assert_eq!(sim.state.get(stopped_at), Some(&2));
+    }
+
+    #[test]
+    fn test_start_stop_once() {
+        let mut sim = Simulation::<u64>::new();
+        let counter = sim.state.insert(0_usize);
+        let stopped_at = sim.state.insert(0_u64);
+        let _ = sim.add_component(StartStopComponent {
+            counter,
+            stopped_at,
+        });
+        sim.stop();
+        assert!(!sim.is_stopped());
+        sim.start();
+        sim.start();
+        assert!(sim.is_started());
+        assert_eq!(sim.scheduler.len(), 2);
+        sim.execute(Executor::unbound()).unwrap();
+        sim.stop();
+        sim.stop();
+        sim.start();
+        assert!(sim.scheduler.is_empty());
+        assert_eq!(sim.state.get(stopped_at), Some(&5));
+    }
+
+    #[test]
+    fn test_try_step_foreign_component() {
+        let mut other = Simulation::<u64>::new();
+        let counter = other.state.insert(0_usize);
+        let _ = other.add_component(TickComponent { counter });
+        let foreign = other.add_component(TickComponent { counter });
+
+        let mut sim = Simulation::<u64>::new();
+        sim.schedule(1, foreign, TestEvent);
+        assert!(matches!(
+            sim.try_step(),
+            Err(crate::SimError::ForeignComponent { component }) if component == foreign.idx()
+        ));
+        assert!(matches!(sim.try_step(), Ok(false)));
+    }
+
+    #[test]
+    fn test_identical_ids() {
+        let build = || {
+            let mut sim = Simulation::<u64>::new();
+            let counter = sim.state.insert(0_usize);
+            let queue = sim.add_queue(crate::Fifo::<usize>::default());
+            let component = sim.add_component(TickComponent { counter });
+            (sim, counter, queue, component)
+        };
+        let (mut first, first_counter, first_queue, first_component) = build();
+        let (second, second_counter, second_queue, second_component) = build();
+        assert_eq!(first_counter.id, second_counter.id);
+        assert_eq!(first_queue.id, second_queue.id);
+        assert_eq!(first_component.idx(), second_component.idx());
+        assert_eq!(first.state.get(second_counter), None);
+        assert!(!second.state.owns(first_counter));
+        assert!(matches!(
+            first.state.try_queue(second_queue),
+            Err(crate::SimError::ForeignQueue { .. })
+        ));
+        assert!(!first.remove_component(second_component));
+        assert_eq!(first.state.get(first_counter), Some(&0));
+    }
+
+    #[derive(Debug)]
+    struct LimitExceeded;
+
+    impl std::fmt::Display for LimitExceeded {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "limit exceeded")
+        }
+    }
+
+    impl std::error::Error for LimitExceeded {}
+
+    struct LimitedComponent {
+        remaining: usize,
+        stopped: crate::Key<bool>,
+    }
+
+    impl crate::TryComponent<u64> for LimitedComponent {
+        type Event = TestEvent;
+        type Error = LimitExceeded;
+
+        fn process_event(
+            &mut self,
+            self_id: crate::ComponentId<Self::Event>,
+            _event: &Self::Event,
+            scheduler: &mut crate::Scheduler<u64>,
+            _state: &mut crate::State,
+        ) -> Result<(), LimitExceeded> {
+            self.remaining = self.remaining.checked_sub(1).ok_or(LimitExceeded)?;
+            scheduler.schedule(2, self_id, TestEvent);
+            Ok(())
+        }
+
+        fn on_stop(
+            &mut self,
+            _self_id: crate::ComponentId<Self::Event>,
+            _scheduler: &mut crate::Scheduler<u64>,
+            state: &mut crate::State,
+        ) {
+            *state.get_mut(self.stopped).unwrap() = true;
+        }
+    }
+
+    #[test]
+    fn test_halt_on_error() {
+        let mut sim = Simulation::<u64>::new();
+        let stopped = sim.state.insert(false);
+        let component = sim.add_component(LimitedComponent {
+            remaining: 3,
+            stopped,
+        });
+        sim.schedule(1, component, TestEvent);
+        let err = sim.execute(Executor::timed(100)).unwrap_err();
+        assert_eq!(err.time, 7);
+        assert_eq!(err.component, component.idx());
+        assert!(
+            matches!(err.error, crate::SimError::Component { component, .. } if component == err.component)
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "simulation halted at 7: component {} failed: limit exceeded",
+                component.idx()
+            )
+        );
+        assert_eq!(sim.state.get(stopped), Some(&false));
+        sim.stop();
+        assert_eq!(sim.state.get(stopped), Some(&true));
+        assert_eq!(sim.scheduler.time(), 7);
+        assert!(sim.scheduler.is_empty());
+    }
 }
@@ -0,0 +1,290 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use super::{ClockRef, PushError, Queue, SimTime, Statistic, Tally, TimeWeighted};
+
+/// Element of an [`Instrumented`] queue, stamped with the time at which it was pushed.
+///
+/// Timestamped elements are compared by their values only, so that the inner queue orders
+/// them just like it would order the values, e.g., a [`PriorityQueue`](crate::PriorityQueue)
+/// still serves elements with equal priorities in the order in which they arrived.
+#[derive(Debug, Clone, Copy)]
+pub struct Timestamped<V, T = Duration> {
+    /// The wrapped element.
+    pub value: V,
+    /// The time at which the element was pushed to the queue.
+    pub time: T,
+}
+
+impl<V: PartialEq, T> PartialEq for Timestamped<V, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<V: Eq, T> Eq for Timestamped<V, T> {}
+
+impl<V: Ord, T> PartialOrd for Timestamped<V, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V: Ord, T> Ord for Timestamped<V, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+/// Statistics of a queue collected by [`Instrumented`].
+pub struct QueueStats<T = Duration> {
+    /// Time-weighted length of the queue.
+    pub length: TimeWeighted<T>,
+    /// Time spent in the queue by the elements that have been popped from it.
+    pub waiting_time: Tally,
+    /// Time spent in the queue by the elements that have been evicted from it to make room
+    /// for other elements, see [`OverflowPolicy`](crate::OverflowPolicy).
+    pub evicted_waiting_time: Tally,
+    /// Number of elements accepted by the queue, including the ones that were later evicted.
+    pub arrivals: u64,
+    /// Number of elements popped from the queue.
+    pub departures: u64,
+    /// Number of elements dropped because the queue was full: both the pushed elements
+    /// that were rejected (see [`PushError`]) and the ones evicted to make room for others.
+    pub dropped: u64,
+}
+
+impl<T: SimTime> QueueStats<T> {
+    /// Returns the number of departures per unit of time since the start or the last reset.
+    /// It is `NaN` if no time has elapsed.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn throughput(&self) -> f64 {
+        let elapsed = self.length.elapsed();
+        if elapsed > 0.0 {
+            self.departures as f64 / elapsed
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+impl<T: SimTime> Statistic for QueueStats<T> {
+    #[allow(clippy::cast_precision_loss)]
+    fn summary(&self) -> Vec<(String, f64)> {
+        vec![
+            (String::from("length.mean"), self.length.mean()),
+            (String::from("length.max"), self.length.max()),
+            (String::from("waiting_time.mean"), self.waiting_time.mean()),
+            (String::from("waiting_time.max"), self.waiting_time.max()),
+            (
+                String::from("evicted_waiting_time.mean"),
+                self.evicted_waiting_time.mean(),
+            ),
+            (String::from("arrivals"), self.arrivals as f64),
+            (String::from("departures"), self.departures as f64),
+            (String::from("dropped"), self.dropped as f64),
+            (String::from("throughput"), self.throughput()),
+        ]
+    }
+}
+
+/// Queue wrapper that records the statistics of the wrapped queue, see [`QueueStats`].
+///
+/// The inner queue holds [`Timestamped`] elements, which lets the wrapper measure the waiting
+/// time of each element regardless of the queueing discipline. Therefore, only queues generic
+/// over their elements, such as [`Fifo`](crate::Fifo) or [`PriorityQueue`](crate::PriorityQueue),
+/// can be wrapped. The statistics can be accessed through the state using the ID of the queue.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{Fifo, Instrumented, Queue, Simulation};
+/// let mut simulation = Simulation::default();
+/// let clock = simulation.scheduler.clock();
+/// let queue = simulation.add_queue(Instrumented::new(Fifo::bounded(1), clock));
+/// assert!(simulation.state.send(queue, "A").is_ok());
+/// assert!(simulation.state.send(queue, "B").is_err());
+/// assert_eq!(simulation.state.recv(queue), Some("A"));
+/// let stats = simulation.state.queue(queue).stats();
+/// assert_eq!((stats.arrivals, stats.departures, stats.dropped), (1, 1, 1));
+/// assert_eq!(stats.length.max(), 1.0);
+/// ```
+pub struct Instrumented<Q, T = Duration> {
+    inner: Q,
+    clock: ClockRef<T>,
+    stats: QueueStats<T>,
+}
+
+impl<Q: Queue, T: SimTime> Instrumented<Q, T> {
+    /// Wraps `queue`, using `clock` to timestamp the elements.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(queue: Q, clock: ClockRef<T>) -> Self {
+        let length = TimeWeighted::new(clock.clone(), queue.len() as f64);
+        Self {
+            inner: queue,
+            clock,
+            stats: QueueStats {
+                length,
+                waiting_time: Tally::default(),
+                evicted_waiting_time: Tally::default(),
+                arrivals: 0,
+                departures: 0,
+                dropped: 0,
+            },
+        }
+    }
+
+    /// Returns the statistics collected so far.
+    #[must_use]
+    pub fn stats(&self) -> &QueueStats<T> {
+        &self.stats
+    }
+
+    /// Discards the statistics collected so far, e.g., at the end of a warm-up period.
+    /// The elements currently in the queue are kept.
+    pub fn reset_stats(&mut self) {
+        self.stats.length.reset();
+        self.stats.waiting_time.reset();
+        self.stats.evicted_waiting_time.reset();
+        self.stats.arrivals = 0;
+        self.stats.departures = 0;
+        self.stats.dropped = 0;
+    }
+
+    /// Returns the wrapped queue.
+    #[must_use]
+    pub fn inner(&self) -> &Q {
+        &self.inner
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn update_length(&mut self) {
+        self.stats.length.update(self.inner.len() as f64);
+    }
+}
+
+impl<V, Q, T> Queue for Instrumented<Q, T>
+where
+    Q: Queue<Item = Timestamped<V, T>>,
+    T: SimTime,
+{
+    type Item = V;
+
+    fn push(&mut self, value: V) -> Result<Option<V>, PushError<V>> {
+        let now = self.clock.time();
+        match self.inner.push(Timestamped { value, time: now }) {
+            Ok(evicted) => {
+                self.stats.arrivals += 1;
+                self.update_length();
+                Ok(evicted.map(|Timestamped { value, time }| {
+                    self.stats.dropped += 1;
+                    self.stats
+                        .evicted_waiting_time
+                        .add(now.to_f64() - time.to_f64());
+                    value
+                }))
+            }
+            Err(PushError(rejected)) => {
+                self.stats.dropped += 1;
+                Err(PushError(rejected.value))
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<V> {
+        let Timestamped { value, time } = self.inner.pop()?;
+        let now = self.clock.time();
+        self.stats.departures += 1;
+        self.stats.waiting_time.add(now.to_f64() - time.to_f64());
+        self.update_length();
+        Some(value)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sync::Clock;
+    use crate::{Fifo, OverflowPolicy, PriorityQueue};
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_instrumented_fifo() {
+        let clock = Clock::new(0_u64);
+        let mut queue = Instrumented::new(Fifo::bounded(2), ClockRef::from(clock.clone()));
+        assert!(queue.push('a').is_ok());
+        clock.set(1);
+        assert!(queue.push('b').is_ok());
+        assert_eq!(queue.push('c'), Err(PushError('c')));
+        clock.set(4);
+        assert_eq!(queue.pop(), Some('a'));
+        clock.set(6);
+        assert_eq!(queue.pop(), Some('b'));
+        assert_eq!(queue.pop(), None);
+        clock.set(8);
+
+        let stats = queue.stats();
+        assert_eq!((stats.arrivals, stats.departures, stats.dropped), (2, 2, 1));
+        assert_eq!(stats.waiting_time.mean(), 4.5);
+        assert_eq!(stats.length.mean(), (1.0 + 2.0 * 3.0 + 2.0) / 8.0);
+        assert_eq!(stats.length.max(), 2.0);
+        assert_eq!(stats.throughput(), 0.25);
+        let summary = stats.summary();
+        assert!(summary.contains(&(String::from("dropped"), 1.0)));
+
+        queue.reset_stats();
+        assert_eq!(queue.stats().arrivals, 0);
+        assert!(queue.stats().throughput().is_nan());
+        assert!(queue.inner().is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_instrumented_eviction() {
+        let clock = Clock::new(0_u64);
+        let mut queue = Instrumented::new(
+            Fifo::bounded_with_policy(1, OverflowPolicy::DropOldest),
+            ClockRef::from(clock.clone()),
+        );
+        assert_eq!(queue.push('a'), Ok(None));
+        clock.set(3);
+        assert_eq!(queue.push('b'), Ok(Some('a')));
+        clock.set(4);
+        assert_eq!(queue.pop(), Some('b'));
+
+        let stats = queue.stats();
+        assert_eq!((stats.arrivals, stats.departures, stats.dropped), (2, 1, 1));
+        assert_eq!(stats.evicted_waiting_time.count(), 1);
+        assert_eq!(stats.evicted_waiting_time.mean(), 3.0);
+        assert_eq!(stats.waiting_time.mean(), 1.0);
+        assert_eq!(stats.length.max(), 1.0);
+    }
+
+    #[test]
+    fn test_instrumented_priority_queue() {
+        let clock = Clock::new(Duration::ZERO);
+        let mut queue = Instrumented::new(PriorityQueue::default(), ClockRef::from(clock.clone()));
+        for (secs, value) in [(0, 1), (1, 2), (2, 1), (3, 2)].iter().copied() {
+            clock.set(Duration::from_secs(secs));
+            queue.push((value, secs)).unwrap();
+        }
+        clock.set(Duration::from_secs(10));
+        let popped: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(popped, vec![(2, 3), (2, 1), (1, 2), (1, 0)]);
+
+        let mut queue = Instrumented::new(PriorityQueue::default(), ClockRef::from(clock.clone()));
+        queue.push(1).unwrap();
+        clock.set(Duration::from_secs(11));
+        queue.push(1).unwrap();
+        clock.set(Duration::from_secs(15));
+        queue.pop();
+        // Elements with equal priorities leave in the order of arrival.
+        assert!((queue.stats().waiting_time.mean() - 5.0).abs() < f64::EPSILON);
+    }
+}
@@ -79,6 +79,45 @@
 //! assert_eq!(state.len(queue_id), 1);
 //! ```
 //!
+//! Besides [`Fifo`] and [`PriorityQueue`], the library provides [`Lifo`], [`RandomOrder`],
+//! [`ProcessorSharing`], and [`MultiClass`] queues, as well as [`DeadlineQueue`], whose elements
+//! leave the queue if they are not served in time. Bounded queues can be configured to drop
+//! elements according to an [`OverflowPolicy`]. [`Fifo`], [`Lifo`], and [`PriorityQueue`]
+//! also implement [`InspectQueue`], which allows to peek at the next element, iterate over
+//! the waiting elements, and remove the ones matching a predicate.
+//!
+//! Queues that accept elements of any type, i.e., [`Fifo`], [`Lifo`], [`RandomOrder`], and
+//! [`PriorityQueue`], can be wrapped in [`Instrumented`] to collect their statistics, such as
+//! the time-weighted length and the waiting times of the elements. The wrapper stores
+//! [`Timestamped`] elements in the inner queue, so it cannot wrap queues whose elements
+//! have a fixed form, such as [`ProcessorSharing`], [`MultiClass`], and [`DeadlineQueue`].
+//!
+//! ## Random Numbers
+//!
+//! [`State`] also provides reproducible random number streams, identified either by a name
+//! or by a [`ComponentId`]. All streams are derived from a single seed, which can be set
+//! with [`Simulation::with_seed`]. Each stream is independent of the others, so adding
+//! a new component does not change the random numbers drawn by the existing ones.
+//!
+//! ```
+//! # use simrs::Simulation;
+//! # use std::time::Duration;
+//! use simrs::rand_core::RngCore;
+//! let mut simulation = Simulation::<Duration>::with_seed(42);
+//! let value = simulation.state.rng_stream("arrivals").next_u64();
+//! let mut other = Simulation::<Duration>::with_seed(42);
+//! assert_eq!(other.state.rng_stream("arrivals").next_u64(), value);
+//! ```
+//!
+//! The [`dist`] module provides common distributions, such as exponential or gamma,
+//! which draw durations directly from these streams.
+//!
+//! ## Statistics
+//!
+//! Statistics such as [`Tally`], [`Histogram`], and [`TimeWeighted`] can be registered
+//! in the state under a name with [`State::register`]. The returned key is used to update
+//! them during the simulation, and [`Simulation::report`] summarizes all of them at the end.
+//!
 //! # Components
 //!
 //! The [`Components`] structure is a container for all registered components.
@@ -119,11 +158,19 @@
 //! # }
 //! ```
 //!
+//! Components that need to mutate their own fields when processing events can implement
+//! [`ComponentMut`] instead, which takes `&mut self`. Every [`Component`] is also a
+//! [`ComponentMut`], so both kinds can be registered in the same container.
+//! Similarly, components whose event processing can fail can implement [`TryComponent`].
+//! An error returned by such a component halts the simulation and is returned from
+//! [`Simulation::execute`] along with the simulation time and the component ID.
+//!
 //! # Scheduler
 //!
 //! The scheduler's main functionality is to keep track of the simulation time and
 //! the future events. Events are scheduled to run on a specific component at a specified
-//! time interval. Because the events are type-erased, it's up to the component to
+//! time interval. Events scheduled for the same time are processed in the order in which
+//! they were scheduled. Because the events are type-erased, it's up to the component to
 //! downcast the event. To make it easy, each component gets a blanket implementation
 //! of an internal trait that does that automatically. It is all encapsulated in the
 //! `Components` container, as shown in the below example:
@@ -172,6 +219,23 @@
 //! # }
 //! ```
 //!
+//! # Simulation Time
+//!
+//! By default, the simulation time is represented by [`Duration`].
+//! However, [`Scheduler`], [`Components`], [`Component`], and [`Simulation`] are all generic
+//! over any type implementing [`SimTime`]. Besides [`Duration`], the library provides
+//! implementations for `u64` (integer ticks) and [`OrderedF64`] (arbitrary real model units).
+//! See [`SimTime`] for an example.
+//!
+//! # Multithreading
+//!
+//! By default, a simulation is meant to run on a single thread, and components, events,
+//! and values in the state can be of any type, e.g., `Rc<RefCell<_>>`.
+//! With the `sync` feature enabled, all these must be [`Send`] (see [`MaybeSend`]),
+//! and in turn, [`Simulation`] is `Send` as well, so it can be built on one thread and
+//! executed on another. In particular, `Replications` runs independent replications
+//! of a model on multiple threads and aggregates their results.
+//!
 //! # Simulation
 //!
 //! [`Simulation`] takes aggregates everything under one structure and provides some additional functions.
@@ -292,62 +356,253 @@
 //!     simulation.schedule(Duration::new(0, 0), producer, ProducerEvent);
 //!     // simulation.schedule(Duration::new(0, 0), consumer, ProducerEvent);
 //!     // The above would fail with:                         ^^^^^^^^^^^^^ expected enum `ConsumerEvent`, found struct `ProducerEvent`
-//!     simulation.execute(Executor::unbound().side_effect(|sim| {
-//!         println!("{:?}", sim.scheduler.time());
-//!     }));
+//!     simulation
+//!         .execute(Executor::unbound().side_effect(|sim| {
+//!             println!("{:?}", sim.scheduler.time());
+//!         }))
+//!         .expect("Simulation failed");
 //! }
 //! ```
 
-use std::cell::Cell;
 use std::marker::PhantomData;
-use std::rc::Rc;
 use std::time::Duration;
 
-type Clock = Rc<Cell<Duration>>;
+use sync::Clock;
 
-pub use component::{Component, Components};
-pub use scheduler::{ClockRef, EventEntry, Scheduler};
+pub use component::{Component, ComponentMut, Components, TryComponent};
+pub use deadline::{DeadlineQueue, Reneged};
+pub use error::{ExecuteError, SimError};
+pub use event_queue::{BinaryHeapQueue, CalendarQueue, EventQueue, PairingHeap};
+pub use instrumented::{Instrumented, QueueStats, Timestamped};
+pub use scheduler::{ClockRef, EventEntry, EventHandle, PeriodicEnd, ScheduleError, Scheduler};
 pub use state::State;
+pub use stats::{Histogram, Statistic, Tally, TimeWeighted};
+pub use sync::MaybeSend;
 
-pub use queue::{Fifo, PriorityQueue, PushError, Queue};
+pub use queue::{
+    Fifo, InspectQueue, Lifo, MultiClass, OverflowPolicy, PriorityIter, PriorityQueue,
+    ProcessorSharing, PushError, Queue, RandomOrder,
+};
+pub use rng::{RngStream, StreamKey};
+pub use time::{OrderedF64, SimTime};
+
+/// Re-export of the [`rand_core`] crate, which defines the [`RngCore`](rand_core::RngCore)
+/// trait implemented by [`RngStream`].
+pub use rand_core;
 
 mod component;
+mod deadline;
+pub mod dist;
+mod error;
+mod event_queue;
 mod execute;
+mod instrumented;
 mod queue;
+#[cfg(feature = "sync")]
+mod replications;
+mod rng;
 mod scheduler;
 mod state;
+mod stats;
+mod sync;
+mod time;
 
 pub use execute::{Execute, Executor};
+#[cfg(feature = "sync")]
+pub use replications::{Replication, Replications, Report, Summary};
 
-static ID_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+static TAG_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 
-fn generate_next_id() -> usize {
-    ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+/// Generates a new tag identifying an ID space, e.g., that of a single simulation.
+///
+/// Only the tags are unique throughout the running of the program; the IDs are allocated
+/// independently in each ID space, so identically built simulations get identical IDs.
+fn generate_tag() -> usize {
+    TAG_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
 }
 
 /// Simulation struct that puts different parts of the simulation together.
 ///
+/// The simulation is generic over the time type `T`, which defaults to [`Duration`].
+/// Use [`Simulation::new`] to create a simulation with a different [`SimTime`].
+///
+/// It is also generic over the [`EventQueue`] `Q` used by the scheduler, which defaults to
+/// [`BinaryHeapQueue`]. Use [`Simulation::with_queue`] to create a simulation with a different
+/// queue. Note that the components of such a simulation must implement [`Component`]
+/// (or [`ComponentMut`] or [`TryComponent`]) for that queue type.
+///
 /// See the [crate-level documentation](index.html) for more information.
-#[derive(Default)]
-pub struct Simulation {
+pub struct Simulation<T = Duration, Q = BinaryHeapQueue<T>> {
     /// Simulation state.
     pub state: State,
     /// Event scheduler.
-    pub scheduler: Scheduler,
+    pub scheduler: Scheduler<T, Q>,
     /// Component container.
-    pub components: Components,
+    pub components: Components<T, Q>,
+    started: bool,
+    stopped: bool,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// `Default` is implemented only for `Duration` so that the time type can be inferred.
+#[allow(clippy::new_without_default)]
+impl<T: SimTime> Simulation<T> {
+    /// Creates a new, empty simulation with the clock set to `T::default()`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Creates a new, empty simulation whose random number streams are derived from `seed`.
+    /// See [`State::rng_stream`] for more information.
+    ///
+    /// A simulation created with [`Simulation::new`] uses the seed `0`.
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_queue(seed, BinaryHeapQueue::default())
+    }
 }
 
-impl Simulation {
+impl<T: SimTime, Q: EventQueue<T>> Simulation<T, Q> {
+    /// Creates a new, empty simulation whose scheduler stores the upcoming events in `queue`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{CalendarQueue, Component, ComponentId, EventQueue, Scheduler, Simulation, State};
+    /// # use std::time::Duration;
+    /// struct Ticker;
+    ///
+    /// impl<Q: EventQueue> Component<Duration, Q> for Ticker {
+    ///     type Event = ();
+    ///     fn process_event(
+    ///         &self,
+    ///         self_id: ComponentId<()>,
+    ///         _event: &(),
+    ///         scheduler: &mut Scheduler<Duration, Q>,
+    ///         _state: &mut State,
+    ///     ) {
+    ///         if scheduler.time() < Duration::from_secs(3) {
+    ///             scheduler.schedule(Duration::from_secs(1), self_id, ());
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut simulation = Simulation::with_queue(CalendarQueue::default());
+    /// let ticker = simulation.add_component(Ticker);
+    /// simulation.schedule(Duration::default(), ticker, ());
+    /// while simulation.step() {}
+    /// assert_eq!(simulation.scheduler.time(), Duration::from_secs(3));
+    /// ```
+    #[must_use]
+    pub fn with_queue(queue: Q) -> Self {
+        Self::with_seed_and_queue(0, queue)
+    }
+
+    /// Creates a new, empty simulation whose random number streams are derived from `seed`,
+    /// and whose scheduler stores the upcoming events in `queue`.
+    #[must_use]
+    pub fn with_seed_and_queue(seed: u64, queue: Q) -> Self {
+        let tag = generate_tag();
+        Self {
+            state: State::with_tag_and_seed(tag, seed),
+            scheduler: Scheduler::with_queue(queue),
+            components: Components::with_tag(tag),
+            started: false,
+            stopped: false,
+        }
+    }
+
+    /// Starts the simulation by calling [`Component::on_start`] on all components, in the order
+    /// in which they were added.
+    ///
+    /// The hooks are called only once per simulation: calling this method again, or after
+    /// [`Simulation::stop`], does nothing. [`Simulation::execute`] starts the simulation
+    /// automatically, so this method only needs to be called when the simulation
+    /// is driven manually, e.g., by [`Simulation::step`].
+    pub fn start(&mut self) {
+        if !self.started {
+            self.started = true;
+            self.components
+                .on_start(&mut self.scheduler, &mut self.state);
+        }
+    }
+
+    /// Stops the simulation by calling [`Component::on_stop`] on all components, in the order
+    /// in which they were added.
+    ///
+    /// The hooks are called only once per simulation, and only if it has been started.
+    /// Calling this method again does nothing. [`Simulation::execute`] stops the simulation
+    /// automatically once the execution ends, so this method only needs to be called when
+    /// the simulation is driven manually, e.g., by [`Simulation::step`], or when
+    /// the execution halted with an error.
+    pub fn stop(&mut self) {
+        if self.started && !self.stopped {
+            self.stopped = true;
+            self.components
+                .on_stop(&mut self.scheduler, &mut self.state);
+        }
+    }
+
+    /// Returns `true` if the simulation has been started. See [`Simulation::start`].
+    #[must_use]
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Returns `true` if the simulation has been stopped. See [`Simulation::stop`].
+    #[must_use]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
     /// Performs one step of the simulation. Returns `true` if there was in fact an event
     /// available to process, and `false` otherwise, which signifies that the simulation
     /// ended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event cannot be dispatched, e.g., its component does not exist.
+    /// See [`Simulation::try_step`] for a fallible version.
     pub fn step(&mut self) -> bool {
-        self.scheduler.pop().map_or(false, |event| {
-            self.components
-                .process_event_entry(event, &mut self.scheduler, &mut self.state);
-            true
-        })
+        self.try_step()
+            .unwrap_or_else(|err| panic!("Failed to process event: {}", err))
+    }
+
+    /// Performs one step of the simulation, just like [`Simulation::step`], but returns
+    /// an error instead of panicking if the event cannot be dispatched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component of the next event does not exist, e.g., its ID
+    /// was issued by another simulation, if the event type does not match the component,
+    /// or if a [`TryComponent`] failed to process the event.
+    pub fn try_step(&mut self) -> Result<bool, SimError> {
+        self.step_or_halt().map_err(|err| err.error)
+    }
+
+    /// Performs one step of the simulation, and in case of failure, returns the error
+    /// along with the time and the component of the failed event.
+    pub(crate) fn step_or_halt(&mut self) -> Result<bool, ExecuteError<T>> {
+        match self.scheduler.pop() {
+            Some(event) => {
+                let component = event.component_idx();
+                self.components
+                    .try_process_event_entry(event, &mut self.scheduler, &mut self.state)
+                    .map_err(|error| ExecuteError {
+                        time: self.scheduler.time(),
+                        component,
+                        error,
+                    })?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
 
     /// Runs the entire simulation from start to end.
@@ -357,7 +612,7 @@ impl Simulation {
         note = "Handling this in Simulation is susceptible to API breaks and/or making it messy. \
                 Use execute instead, which delegates the logic to an external executor."
     )]
-    pub fn run<F: Fn(&Simulation)>(&mut self, step_function: F) {
+    pub fn run<F: Fn(&Simulation<T, Q>)>(&mut self, step_function: F) {
         while self.step() {
             step_function(self);
         }
@@ -367,33 +622,102 @@ impl Simulation {
     ///
     /// The stopping condition and other execution details depend on the executor used.
     /// See [`Execute`] and [`Executor`] for more details.
-    pub fn execute<E: Execute>(&mut self, executor: E) {
-        executor.execute(self);
+    ///
+    /// The simulation is started before the execution, which calls [`Component::on_start`]
+    /// on all components, and it is stopped once the execution ends, e.g., the event queue
+    /// is drained or the bound of the executor is reached, which calls [`Component::on_stop`].
+    /// Both hooks are called only once per simulation, so further calls only continue
+    /// the execution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if processing any event fails, e.g., a [`TryComponent`] returns
+    /// an error. The execution halts at the failed event, and the returned error records
+    /// the time and the component of that event. In that case, the simulation is not stopped.
+    pub fn execute<E: Execute<T, Q>>(&mut self, executor: E) -> Result<(), ExecuteError<T>> {
+        self.start();
+        executor.execute(self)?;
+        self.stop();
+        Ok(())
     }
 
     /// Adds a new component.
     #[must_use]
-    pub fn add_component<E: std::fmt::Debug + 'static, C: Component<Event = E> + 'static>(
-        &mut self,
-        component: C,
-    ) -> ComponentId<E> {
+    pub fn add_component<E, C>(&mut self, component: C) -> ComponentId<E>
+    where
+        E: std::fmt::Debug + MaybeSend + 'static,
+        C: TryComponent<T, Q, Event = E> + MaybeSend + 'static,
+    {
         self.components.add_component(component)
     }
 
+    /// Removes a component along with all its pending events.
+    /// Returns `true` if the component was registered, and `false` otherwise.
+    pub fn remove_component<E: std::fmt::Debug + 'static>(
+        &mut self,
+        component: ComponentId<E>,
+    ) -> bool {
+        self.components
+            .remove_component(component, &mut self.scheduler)
+    }
+
     /// Adds a new unbounded queue.
     #[must_use]
-    pub fn add_queue<Q: Queue + 'static>(&mut self, queue: Q) -> QueueId<Q> {
+    pub fn add_queue<U: Queue + MaybeSend + 'static>(&mut self, queue: U) -> QueueId<U> {
         self.state.add_queue(queue)
     }
 
+    /// Returns the summaries of all statistics registered with [`State::register`],
+    /// keyed by `"{name}.{value}"`. See [`State::report`].
+    ///
+    /// The report can be returned directly as the metrics of a replication.
+    #[must_use]
+    pub fn report(&self) -> std::collections::BTreeMap<String, f64> {
+        self.state.report()
+    }
+
     /// Schedules a new event to be executed at time `time` in component `component`.
-    pub fn schedule<E: std::fmt::Debug + 'static>(
+    /// Returns a handle that can be used to cancel the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is negative or NaN, see [`Scheduler::schedule`].
+    pub fn schedule<E: std::fmt::Debug + MaybeSend + 'static>(
         &mut self,
-        time: Duration,
+        time: T,
         component: ComponentId<E>,
         event: E,
-    ) {
-        self.scheduler.schedule(time, component, event);
+    ) -> EventHandle {
+        self.scheduler.schedule(time, component, event)
+    }
+
+    /// Schedules a new event to be executed at the absolute time `time` in component `component`.
+    /// Returns a handle that can be used to cancel the event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `time` is earlier than the current simulation time.
+    pub fn schedule_at<E: std::fmt::Debug + MaybeSend + 'static>(
+        &mut self,
+        time: T,
+        component: ComponentId<E>,
+        event: E,
+    ) -> Result<EventHandle, ScheduleError<T>> {
+        self.scheduler.schedule_at(time, component, event)
+    }
+
+    /// Schedules a new event to be executed at time `time` in component `component`
+    /// with the given `priority`. See [`Scheduler::schedule_with_priority`] for more details.
+    /// Returns a handle that can be used to cancel the event.
+    pub fn schedule_with_priority<E: std::fmt::Debug + MaybeSend + 'static>(
+        &mut self,
+        time: T,
+        component: ComponentId<E>,
+        event: E,
+        priority: i32,
+    ) -> EventHandle {
+        self.scheduler
+            .schedule_with_priority(time, component, event, priority)
     }
 }
 
@@ -403,12 +727,14 @@ macro_rules! key_type {
         #[doc = $doc]
         #[derive(Debug, PartialEq, Eq, Hash)]
         pub struct $name<V> {
+            pub(crate) tag: usize,
             pub(crate) id: $inner,
             _marker: PhantomData<V>,
         }
         impl<T> $name<T> {
-            pub(crate) fn new(id: $inner) -> Self {
+            pub(crate) fn new(tag: usize, id: $inner) -> Self {
                 $name {
+                    tag,
                     id,
                     _marker: PhantomData,
                 }
@@ -416,7 +742,7 @@ macro_rules! key_type {
         }
         impl<T> Clone for $name<T> {
             fn clone(&self) -> Self {
-                Self::new(self.id)
+                *self
             }
         }
         impl<T> Copy for $name<T> {}
@@ -429,6 +755,20 @@ key_type!(
     "A type-safe identifier of a component. This is an analogue of [`Key`] used specifically for components."
 );
 
+impl<E> ComponentId<E> {
+    /// Returns the index of the component, which can be compared with
+    /// [`EventEntry::component_idx`].
+    #[must_use]
+    pub fn idx(self) -> usize {
+        self.id
+    }
+
+    /// Changes the event type, keeping the ID.
+    pub(crate) fn cast<U>(self) -> ComponentId<U> {
+        ComponentId::new(self.tag, self.id)
+    }
+}
+
 key_type!(
     Key,
     usize,
@@ -437,10 +777,16 @@ key_type!(
 # Construction
 
 A key can be constructed only by calling [`State::insert`].
-The state assigns a new numerical ID to the inserted value, which is unique throughout
-the running of the program.
+The state assigns a new numerical ID to the inserted value, which is unique within that state.
 This ensures type safety, as explained below.
 
+# Ownership
+
+Each key is tagged with the simulation that issued it, so a key from one simulation
+never accesses a value from another one. Instead, [`State::get`] and the like return `None`.
+At the same time, the IDs are allocated independently by each simulation, so two identically
+built simulations assign identical IDs.
+
 # Type Safety
 
 These keys are type-safe in a sense that a key used to insert a value of type `T` cannot be
@@ -461,5 +807,5 @@ let _ = state.remove::<i32>(id);        // Error!
 key_type!(
     QueueId,
     usize,
-    r#"A type-safe identifier of a queue. This is an analogue of [`Key`] used specifically for queues."#
+    "A type-safe identifier of a queue. This is an analogue of [`Key`] used specifically for queues."
 );
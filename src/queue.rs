@@ -1,17 +1,59 @@
-use std::collections::{BinaryHeap, VecDeque};
+use std::cmp::Ordering;
+use std::collections::{binary_heap, vec_deque, BinaryHeap, VecDeque};
+use std::fmt;
+use std::iter::{FusedIterator, Rev};
+use std::time::Duration;
 
-/// Error return when an attempt to push an element to a queue fails due to the queue having
-/// reached its capacity.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub struct PushError;
+use crate::{ClockRef, RngStream, SimTime};
 
-impl std::fmt::Display for PushError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Error returned when a pushed element is rejected because a queue has reached its capacity.
+///
+/// Similarly to [`std::sync::mpsc::SendError`], it carries the rejected element, so that it can be,
+/// e.g., rerouted to another queue or recorded as lost. An element evicted to make room
+/// for the pushed one is not an error; it is returned by [`Queue::push`] in `Ok(Some(_))` instead.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct PushError<T>(pub T);
+
+impl<T> PushError<T> {
+    /// Returns the dropped element.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PushError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "queue reached its capacity")
     }
 }
 
-impl std::error::Error for PushError {}
+impl<T> std::error::Error for PushError<T> {}
+
+/// Policy determining which element is dropped when an element is pushed to a full queue.
+/// A rejected pushed element is returned in a [`PushError`], while an evicted element
+/// is returned in `Ok(Some(_))`, see [`Queue::push`].
+#[derive(Debug, Clone, Default)]
+pub enum OverflowPolicy {
+    /// The pushed element is rejected. This is the default policy.
+    #[default]
+    RejectNew,
+    /// The element that has been in the queue the longest is dropped, and the pushed one is accepted.
+    DropOldest,
+    /// The element with the lowest priority, including the pushed one, is dropped:
+    /// either the pushed element is rejected, or the lowest priority one is evicted.
+    /// It is supported only by [`PriorityQueue`].
+    DropLowestPriority,
+    /// An element chosen uniformly at random from the queue is dropped, and the pushed one is accepted.
+    /// The random choices are drawn from the given stream, e.g., a clone of a dedicated stream
+    /// obtained from [`State::rng_stream`](crate::State::rng_stream).
+    ReplaceRandom(RngStream),
+}
 
 /// Trait implemented by the queues used in the simulation.
 pub trait Queue {
@@ -20,10 +62,14 @@ pub trait Queue {
 
     /// Add an element to the queue.
     ///
+    /// Returns `Ok(None)` if the element was accepted, or `Ok(Some(evicted))` if it was accepted
+    /// but another element was evicted to make room for it, see [`OverflowPolicy`].
+    ///
     /// # Errors
     ///
-    /// Returns an error if the queue is bounded in size and full.
-    fn push(&mut self, value: Self::Item) -> Result<(), PushError>;
+    /// Returns an error carrying the pushed element if the queue is bounded in size and full,
+    /// and the element was rejected, see [`PushError`].
+    fn push(&mut self, value: Self::Item) -> Result<Option<Self::Item>, PushError<Self::Item>>;
 
     /// Removes the next element and returns it, or `None` if the `Queue` is empty.
     fn pop(&mut self) -> Option<Self::Item>;
@@ -37,6 +83,47 @@ pub trait Queue {
     }
 }
 
+/// Extension of [`Queue`] for queues whose waiting elements can be inspected and removed
+/// out of order, e.g., to compute the total work in the queue or to withdraw a cancelled job.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{Fifo, InspectQueue, Queue};
+/// let mut queue = Fifo::default();
+/// for job in 1..=4 {
+///     queue.push(job).unwrap();
+/// }
+/// assert_eq!(queue.peek(), Some(&1));
+/// assert_eq!(queue.iter().sum::<i32>(), 10);
+/// assert_eq!(queue.remove_where(|&job| job % 2 == 0), vec![2, 4]);
+/// assert_eq!(queue.len(), 2);
+/// ```
+pub trait InspectQueue: Queue {
+    /// Iterator over the waiting elements, returned by [`InspectQueue::iter`].
+    type Iter<'a>: Iterator<Item = &'a Self::Item>
+    where
+        Self: 'a;
+
+    /// Returns a reference to the element that would be popped next,
+    /// or `None` if the queue is empty.
+    fn peek(&self) -> Option<&Self::Item>;
+
+    /// Returns an iterator over the waiting elements without allocating.
+    ///
+    /// The order of the elements depends on the queue: [`Fifo`] and [`Lifo`] visit them
+    /// in the order in which they would be popped, while [`PriorityQueue`] visits them
+    /// in an arbitrary order (see [`PriorityQueue::iter_sorted`]).
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Removes all elements for which `predicate` returns `true`, and returns them
+    /// in the order in which they would be popped. The order of the remaining elements
+    /// is unchanged.
+    fn remove_where<F>(&mut self, predicate: F) -> Vec<Self::Item>
+    where
+        F: FnMut(&Self::Item) -> bool;
+}
+
 /// Abstraction over [`VecDeque`] that allows to limit the capacity of the queue.
 /// This means that push operations can fail.
 /// By default, the capacity is equal to [`usize::MAX`], which makes unlimited in practice.
@@ -46,6 +133,7 @@ pub trait Queue {
 pub struct Fifo<T> {
     inner: VecDeque<T>,
     capacity: usize,
+    policy: OverflowPolicy,
 }
 
 impl<T> Default for Fifo<T> {
@@ -53,6 +141,7 @@ impl<T> Default for Fifo<T> {
         Self {
             inner: VecDeque::default(),
             capacity: usize::MAX,
+            policy: OverflowPolicy::RejectNew,
         }
     }
 }
@@ -61,9 +150,25 @@ impl<T> Fifo<T> {
     /// Creates a new queue with limited capacity.
     #[must_use]
     pub fn bounded(capacity: usize) -> Self {
+        Self::bounded_with_policy(capacity, OverflowPolicy::RejectNew)
+    }
+
+    /// Creates a new queue with limited capacity, which handles overflows according to `policy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `policy` is [`OverflowPolicy::DropLowestPriority`], which is not supported
+    /// by a FIFO queue.
+    #[must_use]
+    pub fn bounded_with_policy(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(
+            !matches!(policy, OverflowPolicy::DropLowestPriority),
+            "FIFO queue does not support dropping the lowest priority element."
+        );
         Self {
             inner: VecDeque::with_capacity(capacity),
             capacity,
+            policy,
         }
     }
 }
@@ -71,17 +176,221 @@ impl<T> Fifo<T> {
 impl<T> Queue for Fifo<T> {
     type Item = T;
 
-    fn push(&mut self, value: T) -> Result<(), PushError> {
+    fn push(&mut self, value: T) -> Result<Option<T>, PushError<T>> {
+        push_to_deque(&mut self.inner, self.capacity, &mut self.policy, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.inner.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T> InspectQueue for Fifo<T> {
+    type Iter<'a>
+        = vec_deque::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn peek(&self) -> Option<&T> {
+        self.inner.front()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.inner.iter()
+    }
+
+    fn remove_where<F>(&mut self, predicate: F) -> Vec<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        remove_from_deque(&mut self.inner, predicate).into()
+    }
+}
+
+/// Removes the elements of `deque` matching `predicate`, keeping the order of both
+/// the removed and the remaining ones.
+fn remove_from_deque<T, F>(deque: &mut VecDeque<T>, predicate: F) -> VecDeque<T>
+where
+    F: FnMut(&T) -> bool,
+{
+    let (removed, remaining) = std::mem::take(deque).into_iter().partition(predicate);
+    *deque = remaining;
+    removed
+}
+
+/// Pushes `value` to the back of `deque`, dropping an element according to `policy`
+/// if the deque has reached its `capacity`. The oldest element is at the front.
+fn push_to_deque<T>(
+    deque: &mut VecDeque<T>,
+    capacity: usize,
+    policy: &mut OverflowPolicy,
+    value: T,
+) -> Result<Option<T>, PushError<T>> {
+    if deque.len() < capacity {
+        deque.push_back(value);
+        return Ok(None);
+    }
+    let evicted = match policy {
+        OverflowPolicy::RejectNew | OverflowPolicy::DropLowestPriority => None,
+        OverflowPolicy::DropOldest => deque.pop_front(),
+        OverflowPolicy::ReplaceRandom(rng) => {
+            let len = deque.len();
+            if len > 0 {
+                deque.remove(rng.index(len))
+            } else {
+                None
+            }
+        }
+    };
+    match evicted {
+        Some(evicted) => {
+            deque.push_back(value);
+            Ok(Some(evicted))
+        }
+        None => Err(PushError(value)),
+    }
+}
+
+/// Last-in-first-out queue, i.e., a stack, which allows to limit its capacity
+/// just like [`Fifo`].
+pub struct Lifo<T> {
+    inner: VecDeque<T>,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T> Default for Lifo<T> {
+    fn default() -> Self {
+        Self {
+            inner: VecDeque::default(),
+            capacity: usize::MAX,
+            policy: OverflowPolicy::RejectNew,
+        }
+    }
+}
+
+impl<T> Lifo<T> {
+    /// Creates a new queue with limited capacity.
+    #[must_use]
+    pub fn bounded(capacity: usize) -> Self {
+        Self::bounded_with_policy(capacity, OverflowPolicy::RejectNew)
+    }
+
+    /// Creates a new queue with limited capacity, which handles overflows according to `policy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `policy` is [`OverflowPolicy::DropLowestPriority`], which is not supported
+    /// by a LIFO queue.
+    #[must_use]
+    pub fn bounded_with_policy(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(
+            !matches!(policy, OverflowPolicy::DropLowestPriority),
+            "LIFO queue does not support dropping the lowest priority element."
+        );
+        Self {
+            inner: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+        }
+    }
+}
+
+impl<T> Queue for Lifo<T> {
+    type Item = T;
+
+    fn push(&mut self, value: T) -> Result<Option<T>, PushError<T>> {
+        push_to_deque(&mut self.inner, self.capacity, &mut self.policy, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.inner.pop_back()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T> InspectQueue for Lifo<T> {
+    type Iter<'a>
+        = Rev<vec_deque::Iter<'a, T>>
+    where
+        T: 'a;
+
+    fn peek(&self) -> Option<&T> {
+        self.inner.back()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.inner.iter().rev()
+    }
+
+    fn remove_where<F>(&mut self, predicate: F) -> Vec<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        remove_from_deque(&mut self.inner, predicate)
+            .into_iter()
+            .rev()
+            .collect()
+    }
+}
+
+/// Queue serving its elements in random order.
+///
+/// Every pop removes an element chosen uniformly at random, using the given random number stream,
+/// e.g., a clone of a dedicated stream obtained from [`State::rng_stream`](crate::State::rng_stream).
+pub struct RandomOrder<T> {
+    inner: Vec<T>,
+    capacity: usize,
+    rng: RngStream,
+}
+
+impl<T> RandomOrder<T> {
+    /// Creates a new unbounded queue.
+    #[must_use]
+    pub fn new(rng: RngStream) -> Self {
+        Self {
+            inner: Vec::new(),
+            capacity: usize::MAX,
+            rng,
+        }
+    }
+
+    /// Creates a new queue with limited capacity.
+    #[must_use]
+    pub fn bounded(capacity: usize, rng: RngStream) -> Self {
+        Self {
+            inner: Vec::with_capacity(capacity),
+            capacity,
+            rng,
+        }
+    }
+}
+
+impl<T> Queue for RandomOrder<T> {
+    type Item = T;
+
+    fn push(&mut self, value: T) -> Result<Option<T>, PushError<T>> {
         if self.inner.len() < self.capacity {
-            self.inner.push_back(value);
-            Ok(())
+            self.inner.push(value);
+            Ok(None)
         } else {
-            Err(PushError)
+            Err(PushError(value))
         }
     }
 
     fn pop(&mut self) -> Option<T> {
-        self.inner.pop_front()
+        if self.inner.is_empty() {
+            return None;
+        }
+        let idx = self.rng.index(self.inner.len());
+        Some(self.inner.swap_remove(idx))
     }
 
     fn len(&self) -> usize {
@@ -89,10 +398,44 @@ impl<T> Queue for Fifo<T> {
     }
 }
 
+/// Element of a [`PriorityQueue`] along with its arrival sequence number.
+struct Prioritized<T> {
+    value: T,
+    seq: u64,
+}
+
+impl<T: Ord> PartialEq for Prioritized<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Prioritized<T> {}
+
+impl<T: Ord> PartialOrd for Prioritized<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Prioritized<T> {
+    /// Greater values have higher priority, and among equal values, the earlier one does.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value
+            .cmp(&other.value)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
 /// Binary heap implementation of [`Queue`].
+///
+/// The greatest element is popped first, and elements that are equal are popped
+/// in the order in which they were pushed.
 pub struct PriorityQueue<T> {
-    inner: BinaryHeap<T>,
+    inner: BinaryHeap<Prioritized<T>>,
     capacity: usize,
+    policy: OverflowPolicy,
+    next_seq: u64,
 }
 
 impl<T: Ord> Default for PriorityQueue<T> {
@@ -100,6 +443,8 @@ impl<T: Ord> Default for PriorityQueue<T> {
         Self {
             inner: BinaryHeap::default(),
             capacity: usize::MAX,
+            policy: OverflowPolicy::RejectNew,
+            next_seq: 0,
         }
     }
 }
@@ -108,27 +453,92 @@ impl<T: Ord> PriorityQueue<T> {
     /// Creates a new queue with limited capacity.
     #[must_use]
     pub fn bounded(capacity: usize) -> Self {
+        Self::bounded_with_policy(capacity, OverflowPolicy::RejectNew)
+    }
+
+    /// Creates a new queue with limited capacity, which handles overflows according to `policy`.
+    #[must_use]
+    pub fn bounded_with_policy(capacity: usize, policy: OverflowPolicy) -> Self {
         Self {
             inner: BinaryHeap::with_capacity(capacity),
             capacity,
+            policy,
+            next_seq: 0,
         }
     }
+
+    /// Removes the element at the given position of the underlying heap.
+    /// This takes linear time, but it is only done when the queue overflows.
+    fn remove_at(&mut self, position: usize) -> T {
+        let mut elements = std::mem::take(&mut self.inner).into_vec();
+        let removed = elements.swap_remove(position);
+        self.inner = BinaryHeap::from(elements);
+        removed.value
+    }
+
+    /// Returns an iterator over the elements in the order in which they would be popped.
+    ///
+    /// Unlike [`InspectQueue::iter`], which visits the elements in an arbitrary order,
+    /// this collects and sorts references to them, which takes `O(n log n)` time.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = &T> + '_ {
+        let mut elements: Vec<_> = self.inner.iter().collect();
+        elements.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+        elements.into_iter().map(|element| &element.value)
+    }
+
+    /// Returns the position of the minimal element of the underlying heap according to `compare`.
+    fn position_of_min<F>(&self, compare: F) -> Option<usize>
+    where
+        F: Fn(&Prioritized<T>, &Prioritized<T>) -> Ordering,
+    {
+        self.inner
+            .iter()
+            .enumerate()
+            .min_by(|(_, lhs), (_, rhs)| compare(lhs, rhs))
+            .map(|(position, _)| position)
+    }
 }
 
 impl<T: Ord> Queue for PriorityQueue<T> {
     type Item = T;
 
-    fn push(&mut self, value: T) -> Result<(), PushError> {
+    fn push(&mut self, value: T) -> Result<Option<T>, PushError<T>> {
+        let element = Prioritized {
+            value,
+            seq: self.next_seq,
+        };
+        self.next_seq += 1;
         if self.inner.len() < self.capacity {
-            self.inner.push(value);
-            Ok(())
-        } else {
-            Err(PushError)
+            self.inner.push(element);
+            return Ok(None);
+        }
+        let position = match &mut self.policy {
+            OverflowPolicy::RejectNew => None,
+            OverflowPolicy::DropOldest => self.position_of_min(|lhs, rhs| lhs.seq.cmp(&rhs.seq)),
+            OverflowPolicy::DropLowestPriority => self
+                .inner
+                .iter()
+                .enumerate()
+                .min_by(|(_, lhs), (_, rhs)| lhs.cmp(rhs))
+                .filter(|(_, min)| **min < element)
+                .map(|(position, _)| position),
+            OverflowPolicy::ReplaceRandom(rng) => {
+                let len = self.inner.len();
+                (len > 0).then(|| rng.index(len))
+            }
+        };
+        match position {
+            Some(position) => {
+                let evicted = self.remove_at(position);
+                self.inner.push(element);
+                Ok(Some(evicted))
+            }
+            None => Err(PushError(element.value)),
         }
     }
 
     fn pop(&mut self) -> Option<T> {
-        self.inner.pop()
+        self.inner.pop().map(|element| element.value)
     }
 
     fn len(&self) -> usize {
@@ -136,6 +546,324 @@ impl<T: Ord> Queue for PriorityQueue<T> {
     }
 }
 
+/// Iterating over the elements takes linear time, and they are visited in an arbitrary order.
+/// Removing elements takes linear time, plus the time needed to sort the removed elements
+/// in the order of priority.
+impl<T: Ord> InspectQueue for PriorityQueue<T> {
+    type Iter<'a>
+        = PriorityIter<'a, T>
+    where
+        T: 'a;
+
+    fn peek(&self) -> Option<&T> {
+        self.inner.peek().map(|element| &element.value)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        PriorityIter {
+            inner: self.inner.iter(),
+        }
+    }
+
+    fn remove_where<F>(&mut self, mut predicate: F) -> Vec<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (mut removed, remaining): (Vec<_>, Vec<_>) = std::mem::take(&mut self.inner)
+            .into_vec()
+            .into_iter()
+            .partition(|element| predicate(&element.value));
+        self.inner = BinaryHeap::from(remaining);
+        removed.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+        removed.into_iter().map(|element| element.value).collect()
+    }
+}
+
+/// Iterator over the elements of a [`PriorityQueue`] in an arbitrary order.
+///
+/// It is returned by [`InspectQueue::iter`]; see [`PriorityQueue::iter_sorted`]
+/// to visit the elements in the order of priority.
+pub struct PriorityIter<'a, T> {
+    inner: binary_heap::Iter<'a, Prioritized<T>>,
+}
+
+impl<'a, T> Iterator for PriorityIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next().map(|element| &element.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for PriorityIter<'_, T> {}
+
+impl<T> FusedIterator for PriorityIter<'_, T> {}
+
+/// Job of a [`ProcessorSharing`] queue.
+struct Job<V, T> {
+    /// Virtual time at which the job completes.
+    finish: f64,
+    seq: u64,
+    value: V,
+    work: T,
+}
+
+impl<V, T> PartialEq for Job<V, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<V, T> Eq for Job<V, T> {}
+
+impl<V, T> PartialOrd for Job<V, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V, T> Ord for Job<V, T> {
+    /// The job that completes first is the greatest.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .finish
+            .total_cmp(&self.finish)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Queue for modeling a processor-sharing server, which serves all its jobs simultaneously,
+/// each at the rate of `1 / n`, where `n` is the number of jobs.
+///
+/// Elements are pushed along with the amount of work they require, and they are popped
+/// in the order of completion. Because the completion times change with every arrival,
+/// the server component should schedule its next departure after every push and pop,
+/// using the delay returned by [`ProcessorSharing::next_completion`], and cancel the previously
+/// scheduled one.
+///
+/// The amount of work is expressed in the simulation time `T`, which defaults to [`Duration`].
+/// The progress of the jobs is computed in the units of [`SimTime::to_f64`].
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{ProcessorSharing, Queue, Scheduler};
+/// # use std::time::Duration;
+/// let scheduler = Scheduler::default();
+/// let mut queue = ProcessorSharing::new(scheduler.clock());
+/// queue.push(("A", Duration::from_secs(2))).unwrap();
+/// queue.push(("B", Duration::from_secs(1))).unwrap();
+/// // Both jobs are served at half the rate, so B completes in 2 seconds.
+/// assert_eq!(queue.next_completion(), Some(Duration::from_secs(2)));
+/// ```
+pub struct ProcessorSharing<V, T = Duration> {
+    clock: ClockRef<T>,
+    jobs: BinaryHeap<Job<V, T>>,
+    virtual_time: f64,
+    last_update: T,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl<V, T: SimTime> ProcessorSharing<V, T> {
+    /// Creates a new unbounded queue, using `clock` to track the progress of the jobs.
+    #[must_use]
+    pub fn new(clock: ClockRef<T>) -> Self {
+        Self::bounded(usize::MAX, clock)
+    }
+
+    /// Creates a new queue with limited capacity.
+    #[must_use]
+    pub fn bounded(capacity: usize, clock: ClockRef<T>) -> Self {
+        let last_update = clock.time();
+        Self {
+            clock,
+            jobs: BinaryHeap::new(),
+            virtual_time: 0.0,
+            last_update,
+            capacity,
+            next_seq: 0,
+        }
+    }
+
+    /// Returns the time remaining until the next job completes, assuming that no other jobs
+    /// arrive in the meantime, or `None` if the queue is empty.
+    ///
+    /// The time saturates if it exceeds the range of `T`, see [`SimTime::from_f64`].
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn next_completion(&self) -> Option<T> {
+        let job = self.jobs.peek()?;
+        let remaining = (job.finish - self.current_virtual_time()).max(0.0);
+        Some(T::from_f64(remaining * self.jobs.len() as f64))
+    }
+
+    /// Returns the virtual time, which is the amount of work each job received so far.
+    #[allow(clippy::cast_precision_loss)]
+    fn current_virtual_time(&self) -> f64 {
+        if self.jobs.is_empty() {
+            return self.virtual_time;
+        }
+        let elapsed = (self.clock.time().to_f64() - self.last_update.to_f64()).max(0.0);
+        self.virtual_time + elapsed / self.jobs.len() as f64
+    }
+
+    /// Accounts for the work done since the last update.
+    fn advance(&mut self) {
+        self.virtual_time = self.current_virtual_time();
+        self.last_update = self.clock.time();
+    }
+}
+
+impl<V, T: SimTime> Queue for ProcessorSharing<V, T> {
+    /// Element along with the amount of work it requires.
+    type Item = (V, T);
+
+    fn push(&mut self, (value, work): (V, T)) -> Result<Option<(V, T)>, PushError<(V, T)>> {
+        if self.jobs.len() >= self.capacity {
+            return Err(PushError((value, work)));
+        }
+        self.advance();
+        self.jobs.push(Job {
+            finish: self.virtual_time + work.to_f64(),
+            seq: self.next_seq,
+            value,
+            work,
+        });
+        self.next_seq += 1;
+        Ok(None)
+    }
+
+    /// Removes the job that completes first, regardless of whether it has actually completed.
+    fn pop(&mut self) -> Option<(V, T)> {
+        self.advance();
+        self.jobs.pop().map(|job| (job.value, job.work))
+    }
+
+    fn len(&self) -> usize {
+        self.jobs.len()
+    }
+}
+
+/// Queue consisting of several sub-queues, one for each class of elements.
+///
+/// Elements are pushed and popped along with the index of their class. The classes are served
+/// by smooth weighted round-robin, which, in the long run, pops elements from each class
+/// in proportion to its weight, interleaving the classes as evenly as possible.
+/// Only non-empty classes are considered, so the service is work-conserving.
+/// A class can be bounded by using a bounded sub-queue.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{Fifo, MultiClass, Queue};
+/// let mut queue = MultiClass::weighted(vec![(Fifo::default(), 2), (Fifo::bounded(1), 1)]);
+/// for value in 0..3 {
+///     queue.push((0, value)).unwrap();
+/// }
+/// queue.push((1, 10)).unwrap();
+/// assert!(queue.push((1, 11)).is_err());
+/// let served: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+/// assert_eq!(served, vec![(0, 0), (1, 10), (0, 1), (0, 2)]);
+/// ```
+pub struct MultiClass<Q> {
+    classes: Vec<Q>,
+    weights: Vec<i64>,
+    credits: Vec<i64>,
+}
+
+impl<Q: Queue> MultiClass<Q> {
+    /// Creates a queue serving the given sub-queues in round-robin order.
+    #[must_use]
+    pub fn round_robin(classes: Vec<Q>) -> Self {
+        Self::weighted(classes.into_iter().map(|class| (class, 1)).collect())
+    }
+
+    /// Creates a queue serving the given sub-queues in proportion to their weights.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any weight is zero.
+    #[must_use]
+    pub fn weighted(classes: Vec<(Q, u32)>) -> Self {
+        let (classes, weights): (Vec<_>, Vec<_>) = classes
+            .into_iter()
+            .map(|(class, weight)| {
+                assert!(weight > 0, "Class weight must be positive.");
+                (class, i64::from(weight))
+            })
+            .unzip();
+        let credits = vec![0; classes.len()];
+        Self {
+            classes,
+            weights,
+            credits,
+        }
+    }
+
+    /// Returns the sub-queue of the given class.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the class does not exist.
+    #[must_use]
+    pub fn class(&self, class: usize) -> &Q {
+        &self.classes[class]
+    }
+
+    /// Returns the number of classes.
+    #[must_use]
+    pub fn num_classes(&self) -> usize {
+        self.classes.len()
+    }
+}
+
+impl<Q: Queue> Queue for MultiClass<Q> {
+    /// Element along with the index of its class.
+    type Item = (usize, Q::Item);
+
+    /// Pushes the element to the sub-queue of its class.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the class does not exist.
+    fn push(
+        &mut self,
+        (class, value): (usize, Q::Item),
+    ) -> Result<Option<Self::Item>, PushError<Self::Item>> {
+        match self.classes[class].push(value) {
+            Ok(evicted) => Ok(evicted.map(|evicted| (class, evicted))),
+            Err(PushError(rejected)) => Err(PushError((class, rejected))),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        let mut total = 0;
+        let mut selected: Option<usize> = None;
+        for (class, queue) in self.classes.iter().enumerate() {
+            if queue.is_empty() {
+                continue;
+            }
+            self.credits[class] += self.weights[class];
+            total += self.weights[class];
+            if selected.map_or(true, |s| self.credits[class] > self.credits[s]) {
+                selected = Some(class);
+            }
+        }
+        let class = selected?;
+        self.credits[class] -= total;
+        self.classes[class].pop().map(|value| (class, value))
+    }
+
+    fn len(&self) -> usize {
+        self.classes.iter().map(Queue::len).sum()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -170,7 +898,7 @@ mod test {
         let err = queue.push(2).err();
         assert!(err.is_some());
         let err = err.unwrap();
-        assert_eq!(&format!("{}", err), "queue reached its capacity");
+        assert_eq!(&format!("{err}"), "queue reached its capacity");
         assert_eq!(queue.pop(), Some(0));
         assert_eq!(queue.len(), 1);
         assert!(queue.push(2).is_ok());
@@ -183,7 +911,7 @@ mod test {
     }
 
     #[test]
-    fn test_priority_queue() -> Result<(), PushError> {
+    fn test_priority_queue() -> Result<(), PushError<i32>> {
         let queue = PriorityQueue::<i32>::default();
         assert_eq!(queue.capacity, usize::MAX);
         let mut queue = PriorityQueue::<i32>::bounded(2);
@@ -195,7 +923,7 @@ mod test {
         queue.push(2)?;
         assert_eq!(queue.len(), 2);
 
-        assert_eq!(queue.push(2).err(), Some(PushError));
+        assert_eq!(queue.push(2).err(), Some(PushError(2)));
 
         assert_eq!(queue.len(), 2);
         assert_eq!(queue.pop(), Some(2));
@@ -205,4 +933,294 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_fifo_overflow_policies() {
+        let mut queue = Fifo::bounded_with_policy(2, OverflowPolicy::DropOldest);
+        assert!(queue.push(1).is_ok());
+        assert!(queue.push(2).is_ok());
+        assert_eq!(queue.push(3), Ok(Some(1)));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(3));
+
+        let rng = crate::State::default().rng_stream("overflow").clone();
+        let mut queue = Fifo::bounded_with_policy(3, OverflowPolicy::ReplaceRandom(rng));
+        for value in 0..3 {
+            queue.push(value).unwrap();
+        }
+        let dropped = queue.push(3).unwrap().unwrap();
+        assert!(dropped < 3);
+        let mut remaining: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(remaining.pop(), Some(3));
+        assert!(!remaining.contains(&dropped));
+        assert_eq!(remaining.len(), 2);
+
+        let mut queue = Fifo::bounded_with_policy(0, OverflowPolicy::DropOldest);
+        assert_eq!(queue.push(1), Err(PushError(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "FIFO queue does not support")]
+    fn test_fifo_lowest_priority_unsupported() {
+        let _ = Fifo::<i32>::bounded_with_policy(1, OverflowPolicy::DropLowestPriority);
+    }
+
+    #[test]
+    fn test_priority_queue_overflow_policies() {
+        let mut queue = PriorityQueue::bounded_with_policy(2, OverflowPolicy::DropLowestPriority);
+        queue.push(5).unwrap();
+        queue.push(3).unwrap();
+        assert_eq!(queue.push(4), Ok(Some(3)));
+        assert_eq!(queue.push(1), Err(PushError(1)));
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), Some(4));
+
+        let mut queue = PriorityQueue::bounded_with_policy(2, OverflowPolicy::DropOldest);
+        queue.push(3).unwrap();
+        queue.push(5).unwrap();
+        assert_eq!(queue.push(1), Ok(Some(3)));
+        assert_eq!(queue.push(2), Ok(Some(5)));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(1));
+
+        let rng = crate::State::default().rng_stream("overflow").clone();
+        let mut queue = PriorityQueue::bounded_with_policy(2, OverflowPolicy::ReplaceRandom(rng));
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        let dropped = queue.push(3).unwrap().unwrap();
+        assert!(dropped == 1 || dropped == 2);
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(3 - dropped));
+    }
+
+    #[test]
+    fn test_priority_queue_ties() {
+        let mut queue = PriorityQueue::default();
+        for (priority, name) in [(1, "a"), (2, "b"), (1, "c"), (2, "d")].iter().copied() {
+            queue.push(Task { priority, name }).unwrap();
+        }
+        let names: Vec<_> = std::iter::from_fn(|| queue.pop().map(|task| task.name)).collect();
+        assert_eq!(names, vec!["b", "d", "a", "c"]);
+    }
+
+    #[test]
+    fn test_inspect_deques() {
+        let mut queue = Fifo::default();
+        assert_eq!(queue.peek(), None);
+        for value in 1..=5 {
+            queue.push(value).unwrap();
+        }
+        assert_eq!(queue.peek(), Some(&1));
+        assert_eq!(
+            queue.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(queue.remove_where(|&value| value % 2 == 1), vec![1, 3, 5]);
+        assert!(queue.remove_where(|&value| value > 10).is_empty());
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(4));
+
+        let mut queue = Lifo::default();
+        for value in 1..=5 {
+            queue.push(value).unwrap();
+        }
+        assert_eq!(queue.peek(), Some(&5));
+        assert_eq!(
+            queue.iter().copied().collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+        assert_eq!(queue.remove_where(|&value| value % 2 == 1), vec![5, 3, 1]);
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), Some(2));
+    }
+
+    #[test]
+    fn test_inspect_priority_queue() {
+        let mut queue = PriorityQueue::default();
+        assert_eq!(queue.peek(), None);
+        for (priority, name) in [(1, "a"), (3, "b"), (1, "c"), (2, "d"), (3, "e")]
+            .iter()
+            .copied()
+        {
+            queue.push(Task { priority, name }).unwrap();
+        }
+        assert_eq!(queue.peek().map(|task| task.name), Some("b"));
+        let names: Vec<_> = queue.iter_sorted().map(|task| task.name).collect();
+        assert_eq!(names, vec!["b", "e", "d", "a", "c"]);
+        let mut names: Vec<_> = queue.iter().map(|task| task.name).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(queue.iter().len(), 5);
+        let total: u32 = queue.iter().map(|task| task.priority).sum();
+        assert_eq!(total, 10);
+
+        let removed = queue.remove_where(|task| task.name == "e" || task.name == "a");
+        let names: Vec<_> = removed.iter().map(|task| task.name).collect();
+        assert_eq!(names, vec!["e", "a"]);
+        assert_eq!(queue.len(), 3);
+        // Remaining elements with equal priorities still leave in the order of arrival.
+        queue
+            .push(Task {
+                priority: 1,
+                name: "f",
+            })
+            .unwrap();
+        let names: Vec<_> = std::iter::from_fn(|| queue.pop().map(|task| task.name)).collect();
+        assert_eq!(names, vec!["b", "d", "c", "f"]);
+    }
+
+    #[test]
+    fn test_lifo() {
+        let mut queue = Lifo::bounded(2);
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        assert_eq!(queue.push(3), Err(PushError(3)));
+        assert_eq!(queue.pop(), Some(2));
+        queue.push(3).unwrap();
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), None);
+
+        let mut queue = Lifo::bounded_with_policy(2, OverflowPolicy::DropOldest);
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        assert_eq!(queue.push(3), Ok(Some(1)));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(2));
+        assert!(Lifo::<i32>::default().is_empty());
+    }
+
+    #[test]
+    fn test_random_order() {
+        let mut state = crate::State::default();
+        let mut queue = RandomOrder::bounded(10, state.rng_stream("order").clone());
+        for value in 0..10 {
+            queue.push(value).unwrap();
+        }
+        assert_eq!(queue.push(10), Err(PushError(10)));
+        let popped: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_ne!(popped, (0..10).collect::<Vec<_>>());
+        let mut sorted = popped.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+
+        // The order is reproducible.
+        let mut queue = RandomOrder::new(crate::State::default().rng_stream("order").clone());
+        for value in 0..10 {
+            queue.push(value).unwrap();
+        }
+        assert_eq!(
+            std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>(),
+            popped
+        );
+    }
+
+    #[test]
+    fn test_processor_sharing() {
+        let clock = crate::sync::Clock::new(Duration::ZERO);
+        let secs = Duration::from_secs;
+        let mut queue = ProcessorSharing::bounded(2, ClockRef::from(clock.clone()));
+        assert_eq!(queue.next_completion(), None);
+        queue.push(("a", secs(3))).unwrap();
+        assert_eq!(queue.next_completion(), Some(secs(3)));
+
+        // After a second, `a` needs 2 more seconds of work, which now takes 4 seconds.
+        clock.set(secs(1));
+        queue.push(("b", secs(4))).unwrap();
+        assert!(queue.push(("c", secs(1))).is_err());
+        assert_eq!(queue.next_completion(), Some(secs(4)));
+        clock.set(secs(5));
+        assert_eq!(queue.next_completion(), Some(Duration::ZERO));
+        assert_eq!(queue.pop(), Some(("a", secs(3))));
+
+        // `b` has received 2 seconds of work, and it is now served alone.
+        assert_eq!(queue.next_completion(), Some(secs(2)));
+        clock.set(secs(7));
+        assert_eq!(queue.pop(), Some(("b", secs(4))));
+        assert_eq!(queue.pop(), None);
+
+        // Completion times beyond the range of `Duration` saturate.
+        queue.push(("c", Duration::MAX)).unwrap();
+        queue.push(("d", Duration::MAX)).unwrap();
+        assert_eq!(queue.next_completion(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn test_processor_sharing_ticks() {
+        let clock = crate::sync::Clock::new(0_u64);
+        let mut queue = ProcessorSharing::new(ClockRef::from(clock.clone()));
+        queue.push(("a", 4)).unwrap();
+        queue.push(("b", 6)).unwrap();
+        assert_eq!(queue.next_completion(), Some(8));
+        clock.set(8);
+        assert_eq!(queue.pop(), Some(("a", 4)));
+        assert_eq!(queue.next_completion(), Some(2));
+    }
+
+    #[test]
+    fn test_multi_class_round_robin() {
+        let mut queue =
+            MultiClass::round_robin(vec![Fifo::default(), Fifo::default(), Fifo::bounded(1)]);
+        assert_eq!(queue.num_classes(), 3);
+        for value in 0..3 {
+            queue.push((0, value)).unwrap();
+            queue.push((1, value + 10)).unwrap();
+        }
+        queue.push((2, 20)).unwrap();
+        assert_eq!(queue.push((2, 21)), Err(PushError((2, 21))));
+        assert_eq!(queue.len(), 7);
+        assert_eq!(queue.class(1).len(), 3);
+        let served: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(
+            served,
+            vec![(0, 0), (1, 10), (2, 20), (0, 1), (1, 11), (0, 2), (1, 12)]
+        );
+
+        let mut queue = MultiClass::round_robin(vec![
+            Fifo::default(),
+            Fifo::bounded_with_policy(1, OverflowPolicy::DropOldest),
+        ]);
+        assert_eq!(queue.push((1, 10)), Ok(None));
+        assert_eq!(queue.push((1, 11)), Ok(Some((1, 10))));
+    }
+
+    #[test]
+    fn test_multi_class_weighted() {
+        let mut queue = MultiClass::weighted(vec![(Lifo::default(), 3), (Lifo::default(), 1)]);
+        for value in 0..100 {
+            queue.push((value % 2, value)).unwrap();
+        }
+        let first_classes: Vec<_> = (0..40).map(|_| queue.pop().unwrap().0).collect();
+        assert_eq!(
+            first_classes.iter().filter(|&&class| class == 0).count(),
+            30
+        );
+    }
+
+    /// Task ordered only by priority.
+    #[derive(Debug)]
+    struct Task {
+        priority: u32,
+        name: &'static str,
+    }
+
+    impl PartialEq for Task {
+        fn eq(&self, other: &Self) -> bool {
+            self.priority == other.priority
+        }
+    }
+
+    impl Eq for Task {}
+
+    impl PartialOrd for Task {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Task {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.priority.cmp(&other.priority)
+        }
+    }
 }
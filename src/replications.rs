@@ -0,0 +1,579 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::{EventQueue, Execute, ExecuteError, SimTime, Simulation};
+
+/// Runs independent replications of a simulation model in parallel.
+///
+/// Each replication is built from its own seed by a user-provided closure, executed with
+/// an executor created by another closure (see [`Execute`]), and finally, its output metrics
+/// are extracted by a third closure. The replications are distributed among a pool of worker
+/// threads.
+///
+/// Available only with the `sync` feature.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{Component, ComponentId, Executor, Key, Replications, Scheduler, Simulation, State};
+/// # use std::time::Duration;
+/// struct Arrivals {
+///     count: Key<u64>,
+///     interval: Duration,
+/// }
+///
+/// impl Component for Arrivals {
+///     type Event = ();
+///     fn process_event(
+///         &self,
+///         self_id: ComponentId<()>,
+///         _event: &(),
+///         scheduler: &mut Scheduler,
+///         state: &mut State,
+///     ) {
+///         *state.get_mut(self.count).unwrap() += 1;
+///         scheduler.schedule(self.interval, self_id, ());
+///     }
+/// }
+///
+/// let report = Replications::new(10).threads(2).run(
+///     |seed| {
+///         let mut simulation = Simulation::default();
+///         let count = simulation.state.insert(0_u64);
+///         let arrivals = simulation.add_component(Arrivals {
+///             count,
+///             interval: Duration::from_millis(100 + seed),
+///         });
+///         simulation.schedule(Duration::default(), arrivals, ());
+///         (simulation, count)
+///     },
+///     || Executor::timed(Duration::from_secs(1)),
+///     |simulation, count| vec![("arrivals", *simulation.state.get(*count).unwrap() as f64)],
+/// );
+/// assert_eq!(report.replications.len(), 10);
+/// let arrivals = report.summary("arrivals").unwrap();
+/// assert_eq!(arrivals.count, 10);
+/// let (low, high) = arrivals.confidence_interval(0.95);
+/// assert!(low < arrivals.mean && arrivals.mean < high);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Replications {
+    seeds: Vec<u64>,
+    threads: usize,
+}
+
+impl Replications {
+    /// Creates `count` replications with seeds `0..count`.
+    ///
+    /// By default, the number of threads is equal to the available parallelism.
+    #[must_use]
+    pub fn new(count: usize) -> Self {
+        Self::with_seeds((0..count as u64).collect())
+    }
+
+    /// Creates one replication for each of the given seeds.
+    #[must_use]
+    pub fn with_seeds(seeds: Vec<u64>) -> Self {
+        Self {
+            seeds,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    /// Sets the number of worker threads. Zero is treated as one.
+    #[must_use]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Runs all replications and aggregates their results.
+    ///
+    /// - `build` creates the simulation for the given seed, along with any context
+    ///   that is later needed to extract the metrics, such as keys to the state.
+    /// - `executor` creates the executor of each simulation. Because a new executor is created
+    ///   for every replication, it does not need to be [`Clone`]; for example, it can be
+    ///   an executor with a side effect (see [`Executor::side_effect`](crate::Executor::side_effect)).
+    /// - `metrics` extracts named metrics from a finished simulation.
+    ///
+    /// The replications are reported in the order of their seeds, regardless of the order
+    /// in which they finished. If a replication fails, its error is reported,
+    /// and it is excluded from the summaries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the closures panics in a worker thread.
+    pub fn run<T, Q, C, B, F, E, X, M, N>(self, build: B, executor: F, metrics: X) -> Report<T>
+    where
+        T: SimTime,
+        Q: EventQueue<T>,
+        B: Fn(u64) -> (Simulation<T, Q>, C) + Sync,
+        F: Fn() -> E + Sync,
+        E: Execute<T, Q>,
+        X: Fn(&Simulation<T, Q>, &C) -> M + Sync,
+        M: IntoIterator<Item = (N, f64)>,
+        N: Into<String>,
+    {
+        let next = AtomicUsize::new(0);
+        let outcomes = Mutex::new(Vec::with_capacity(self.seeds.len()));
+        let threads = self.threads.min(self.seeds.len());
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&seed) = self.seeds.get(idx) else {
+                        break;
+                    };
+                    let (mut simulation, context) = build(seed);
+                    let outcome = simulation.execute(executor());
+                    simulation.stop();
+                    let outcome = outcome.map(|()| {
+                        metrics(&simulation, &context)
+                            .into_iter()
+                            .map(|(name, value)| (name.into(), value))
+                            .collect()
+                    });
+                    outcomes
+                        .lock()
+                        .expect("Worker thread panicked")
+                        .push((idx, outcome));
+                });
+            }
+        });
+        let mut outcomes = outcomes.into_inner().expect("Worker thread panicked");
+        outcomes.sort_unstable_by_key(|(idx, _)| *idx);
+        let replications: Vec<_> = outcomes
+            .into_iter()
+            .map(|(idx, outcome)| Replication {
+                seed: self.seeds[idx],
+                outcome,
+            })
+            .collect();
+        Report::new(replications)
+    }
+}
+
+/// Result of a single replication.
+#[derive(Debug)]
+pub struct Replication<T = Duration> {
+    /// Seed used to build the simulation.
+    pub seed: u64,
+    /// Extracted metrics, or the error that halted the simulation.
+    pub outcome: Result<BTreeMap<String, f64>, ExecuteError<T>>,
+}
+
+/// Results of all replications along with the summary of each metric.
+#[derive(Debug)]
+pub struct Report<T = Duration> {
+    /// Results of the individual replications, ordered by seed index.
+    pub replications: Vec<Replication<T>>,
+    summaries: BTreeMap<String, Summary>,
+}
+
+impl<T> Report<T> {
+    fn new(replications: Vec<Replication<T>>) -> Self {
+        let mut values = BTreeMap::<String, Vec<f64>>::new();
+        for metrics in replications.iter().filter_map(|r| r.outcome.as_ref().ok()) {
+            for (name, &value) in metrics {
+                values.entry(name.clone()).or_default().push(value);
+            }
+        }
+        let summaries = values
+            .into_iter()
+            .map(|(name, values)| (name, Summary::from_values(&values)))
+            .collect();
+        Self {
+            replications,
+            summaries,
+        }
+    }
+
+    /// Returns the summary of the metric with the given name, or `None` if no successful
+    /// replication reported it.
+    #[must_use]
+    pub fn summary(&self, name: &str) -> Option<&Summary> {
+        self.summaries.get(name)
+    }
+
+    /// Iterates over the summaries of all metrics, ordered by name.
+    pub fn summaries(&self) -> impl Iterator<Item = (&str, &Summary)> {
+        self.summaries
+            .iter()
+            .map(|(name, summary)| (name.as_str(), summary))
+    }
+
+    /// Iterates over the errors of the failed replications, along with their seeds.
+    pub fn errors(&self) -> impl Iterator<Item = (u64, &ExecuteError<T>)> {
+        self.replications
+            .iter()
+            .filter_map(|r| r.outcome.as_ref().err().map(|err| (r.seed, err)))
+    }
+}
+
+/// Summary statistics of a metric across replications.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    /// Number of replications that reported the metric.
+    pub count: usize,
+    /// Sample mean.
+    pub mean: f64,
+    /// Unbiased sample variance. It is `NaN` if there are fewer than two values.
+    pub variance: f64,
+}
+
+impl Summary {
+    /// Computes the summary of the given values.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_values(values: &[f64]) -> Self {
+        let count = values.len();
+        let n = count as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = if count < 2 {
+            f64::NAN
+        } else {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        };
+        Self {
+            count,
+            mean,
+            variance,
+        }
+    }
+
+    /// Sample standard deviation.
+    #[must_use]
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    /// Half-width of the confidence interval of the mean at the given `level`, e.g., `0.95`,
+    /// based on the Student's t-distribution with `count - 1` degrees of freedom.
+    ///
+    /// It is `NaN` if there are fewer than two values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level` is not in the open interval `(0, 1)`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn half_width(&self, level: f64) -> f64 {
+        assert!(
+            level > 0.0 && level < 1.0,
+            "Confidence level must be between 0 and 1."
+        );
+        if self.count < 2 {
+            return f64::NAN;
+        }
+        let n = self.count as f64;
+        student_t_quantile(0.5 + level / 2.0, n - 1.0) * (self.variance / n).sqrt()
+    }
+
+    /// Confidence interval of the mean at the given `level`. See [`Summary::half_width`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level` is not in the open interval `(0, 1)`.
+    #[must_use]
+    pub fn confidence_interval(&self, level: f64) -> (f64, f64) {
+        let half_width = self.half_width(level);
+        (self.mean - half_width, self.mean + half_width)
+    }
+}
+
+/// Quantile of the Student's t-distribution with `df` degrees of freedom for `p` in `[0.5, 1)`.
+fn student_t_quantile(p: f64, df: f64) -> f64 {
+    let mut high = 1.0;
+    while student_t_cdf(high, df) < p {
+        high *= 2.0;
+    }
+    let mut low = 0.0;
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if student_t_cdf(mid, df) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Cumulative distribution function of the Student's t-distribution for `t >= 0`.
+fn student_t_cdf(t: f64, df: f64) -> f64 {
+    1.0 - 0.5 * regularized_incomplete_beta(df / (df + t * t), df / 2.0, 0.5)
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`, evaluated with a continued fraction.
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function, using the modified Lentz's method.
+#[allow(clippy::many_single_char_names)]
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+    for m in 1..300 {
+        let m = f64::from(m);
+        let m2 = 2.0 * m;
+        let numerator = m * (b - m) * x / ((a + m2 - 1.0) * (a + m2));
+        d = 1.0 + numerator * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        result *= d * c;
+        let numerator = -(a + m) * (a + b + m) * x / ((a + m2) * (a + m2 + 1.0));
+        d = 1.0 + numerator * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        result *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    result
+}
+
+/// Natural logarithm of the gamma function for positive arguments (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .zip(1..)
+        .fold(COEFFICIENTS[0], |acc, (&c, i)| acc + c / (x + f64::from(i)));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Component, ComponentId, Executor, Key, Scheduler, State};
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_student_t_quantile() {
+        assert_close(student_t_quantile(0.975, 1.0), 12.706, 1e-3);
+        assert_close(student_t_quantile(0.975, 2.0), 4.303, 1e-3);
+        assert_close(student_t_quantile(0.975, 9.0), 2.262, 1e-3);
+        assert_close(student_t_quantile(0.95, 29.0), 1.699, 1e-3);
+        assert_close(student_t_quantile(0.995, 99.0), 2.626, 1e-3);
+        assert_close(student_t_quantile(0.5, 5.0), 0.0, 1e-9);
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = Summary::from_values(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(summary.count, 8);
+        assert_close(summary.mean, 5.0, 1e-12);
+        assert_close(summary.variance, 32.0 / 7.0, 1e-12);
+        assert_close(summary.std_dev(), (32.0_f64 / 7.0).sqrt(), 1e-12);
+        let half_width = 2.365 * (32.0_f64 / 7.0 / 8.0).sqrt();
+        let (low, high) = summary.confidence_interval(0.95);
+        assert_close(low, 5.0 - half_width, 1e-3);
+        assert_close(high, 5.0 + half_width, 1e-3);
+
+        let single = Summary::from_values(&[1.0]);
+        assert_close(single.mean, 1.0, 1e-12);
+        assert!(single.variance.is_nan());
+        assert!(single.half_width(0.95).is_nan());
+    }
+
+    struct Countdown {
+        remaining: Key<u64>,
+    }
+
+    impl Component for Countdown {
+        type Event = ();
+
+        fn process_event(
+            &self,
+            self_id: ComponentId<()>,
+            _event: &(),
+            scheduler: &mut Scheduler,
+            state: &mut State,
+        ) {
+            let remaining = state.get_mut(self.remaining).unwrap();
+            if *remaining > 0 {
+                *remaining -= 1;
+                scheduler.schedule(Duration::from_secs(1), self_id, ());
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_replications() {
+        let build = |seed: u64| {
+            let mut simulation = Simulation::default();
+            let remaining = simulation.state.insert(seed);
+            let countdown = simulation.add_component(Countdown { remaining });
+            simulation.schedule(Duration::default(), countdown, ());
+            (simulation, ())
+        };
+        let report = Replications::with_seeds(vec![3, 1, 4, 1, 5])
+            .threads(3)
+            .run(build, Executor::unbound, |simulation, ()| {
+                vec![("end", simulation.scheduler.time().as_secs() as f64)]
+            });
+        let seeds: Vec<_> = report.replications.iter().map(|r| r.seed).collect();
+        assert_eq!(seeds, vec![3, 1, 4, 1, 5]);
+        let ends: Vec<_> = report
+            .replications
+            .iter()
+            .map(|r| r.outcome.as_ref().unwrap()["end"])
+            .collect();
+        assert_eq!(ends, vec![3.0, 1.0, 4.0, 1.0, 5.0]);
+        assert_eq!(report.errors().count(), 0);
+        assert_eq!(
+            report.summary("end").copied(),
+            Some(Summary::from_values(&ends))
+        );
+        assert_eq!(report.summaries().count(), 1);
+        assert!(report.summary("missing").is_none());
+    }
+
+    #[test]
+    fn test_executor_with_side_effect() {
+        let steps = AtomicUsize::new(0);
+        let report = Replications::with_seeds(vec![3, 1, 4, 1, 5])
+            .threads(2)
+            .run(
+                |seed: u64| {
+                    let mut simulation = Simulation::default();
+                    let remaining = simulation.state.insert(seed);
+                    let countdown = simulation.add_component(Countdown { remaining });
+                    simulation.schedule(Duration::default(), countdown, ());
+                    (simulation, ())
+                },
+                || {
+                    Executor::unbound().side_effect(|_| {
+                        steps.fetch_add(1, Ordering::Relaxed);
+                    })
+                },
+                |_, ()| Vec::<(&str, f64)>::new(),
+            );
+        assert_eq!(report.errors().count(), 0);
+        assert_eq!(steps.into_inner(), 19);
+    }
+
+    #[derive(Debug)]
+    struct Odd;
+
+    impl std::fmt::Display for Odd {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "odd seed")
+        }
+    }
+
+    impl std::error::Error for Odd {}
+
+    struct EvenOnly(u64);
+
+    impl crate::TryComponent for EvenOnly {
+        type Event = ();
+        type Error = Odd;
+
+        fn process_event(
+            &mut self,
+            _self_id: ComponentId<()>,
+            _event: &(),
+            _scheduler: &mut Scheduler,
+            _state: &mut State,
+        ) -> Result<(), Odd> {
+            if self.0 % 2 == 0 {
+                Ok(())
+            } else {
+                Err(Odd)
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_failed_replications() {
+        let report = Replications::new(6).threads(4).run(
+            |seed| {
+                let mut simulation = Simulation::default();
+                let component = simulation.add_component(EvenOnly(seed));
+                simulation.schedule(Duration::from_secs(seed), component, ());
+                (simulation, seed)
+            },
+            Executor::unbound,
+            |_, &seed| vec![("seed", seed as f64)],
+        );
+        let failed: Vec<_> = report
+            .errors()
+            .map(|(seed, err)| (seed, err.time))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                (1, Duration::from_secs(1)),
+                (3, Duration::from_secs(3)),
+                (5, Duration::from_secs(5))
+            ]
+        );
+        let summary = report.summary("seed").unwrap();
+        assert_eq!(summary.count, 3);
+        assert!((summary.mean - 2.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,103 @@
+use rand_core::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use super::ComponentId;
+
+/// Identifies a random number stream of a [`State`](crate::State).
+///
+/// A stream can be identified either by an arbitrary name or by a component.
+/// Both `&str` and [`ComponentId`] can be converted into a key, so they can be passed directly
+/// to [`State::rng_stream`](crate::State::rng_stream).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StreamKey {
+    /// Stream identified by a name.
+    Name(String),
+    /// Stream identified by the index of a component.
+    Component(usize),
+}
+
+impl From<&str> for StreamKey {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for StreamKey {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl<E> From<ComponentId<E>> for StreamKey {
+    fn from(component: ComponentId<E>) -> Self {
+        Self::Component(component.idx())
+    }
+}
+
+impl StreamKey {
+    /// Derives the seed of this stream from the seed of the simulation.
+    ///
+    /// The derivation depends only on the simulation seed and the key itself,
+    /// and it is stable between runs and platforms, unlike the hashers from `std`.
+    fn derive_seed(&self, seed: u64) -> u64 {
+        let hash = match self {
+            Self::Name(name) => fnv1a(0, name.as_bytes()),
+            Self::Component(idx) => fnv1a(1, &(*idx as u64).to_le_bytes()),
+        };
+        splitmix64(seed ^ splitmix64(hash))
+    }
+}
+
+/// FNV-1a hash of the given bytes, prefixed with a discriminant byte.
+fn fnv1a(discriminant: u8, bytes: &[u8]) -> u64 {
+    std::iter::once(&discriminant)
+        .chain(bytes)
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// Finalizer of the `SplitMix64` generator, which scrambles the bits of the input.
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Reproducible stream of random numbers, see [`State::rng_stream`](crate::State::rng_stream).
+///
+/// It implements [`RngCore`], so it can be used with any sampler built on top of `rand_core`.
+#[derive(Debug, Clone)]
+pub struct RngStream(Xoshiro256PlusPlus);
+
+impl RngStream {
+    /// Creates the stream identified by `key` within a simulation seeded with `seed`.
+    pub(crate) fn new(seed: u64, key: &StreamKey) -> Self {
+        Self(Xoshiro256PlusPlus::seed_from_u64(key.derive_seed(seed)))
+    }
+
+    /// Draws an index uniformly from `0..len` using the multiply-shift method.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn index(&mut self, len: usize) -> usize {
+        ((u128::from(self.0.next_u64()) * len as u128) >> 64) as usize
+    }
+}
+
+impl RngCore for RngStream {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
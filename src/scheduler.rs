@@ -1,12 +1,10 @@
-use std::any::Any;
-use std::cell::Cell;
 use std::cmp::{Ordering, Reverse};
-use std::collections::BinaryHeap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::rc::Rc;
 use std::time::Duration;
 
-use crate::{Clock, ComponentId};
+use crate::sync::{AnyBox, EventFactory};
+use crate::{BinaryHeapQueue, Clock, ComponentId, EventQueue, MaybeSend, SimTime};
 
 /// Entry type stored in the scheduler, including the event value, component ID, and the time when
 /// it is supposed to occur.
@@ -15,21 +13,27 @@ use crate::{Clock, ComponentId};
 /// event entries are simply passed to [`crate::Components`] object, which unpacks them, and passes them
 /// to the correct component.
 #[derive(Debug)]
-pub struct EventEntry {
-    time: Reverse<Duration>,
-    component: usize,
-    inner: Box<dyn Any>,
+pub struct EventEntry<T = Duration> {
+    time: Reverse<T>,
+    component: ComponentId<()>,
+    handle: EventHandle,
+    priority: i32,
+    pub(crate) sequence: usize,
+    inner: AnyBox,
 }
 
-impl EventEntry {
-    pub(crate) fn new<E: fmt::Debug + 'static>(
-        time: Duration,
+impl<T: SimTime> EventEntry<T> {
+    pub(crate) fn new<E: fmt::Debug + MaybeSend + 'static>(
+        time: T,
         component: ComponentId<E>,
         event: E,
     ) -> Self {
         EventEntry {
             time: Reverse(time),
-            component: component.id,
+            component: component.cast(),
+            handle: EventHandle(0),
+            priority: 0,
+            sequence: 0,
             inner: Box::new(event),
         }
     }
@@ -39,52 +43,105 @@ impl EventEntry {
     #[must_use]
     pub(crate) fn downcast<E: fmt::Debug + 'static>(&self) -> Option<EventEntryTyped<'_, E>> {
         self.inner.downcast_ref::<E>().map(|event| EventEntryTyped {
-            time: self.time.0,
-            component_id: ComponentId::new(self.component),
-            component_idx: self.component,
+            component_id: self.component.cast(),
             event,
         })
     }
 
+    /// Returns a reference to the event if it is of type `E`, or `None` otherwise.
     #[must_use]
-    pub(crate) fn component_idx(&self) -> usize {
+    pub fn downcast_ref<E: fmt::Debug + 'static>(&self) -> Option<&E> {
+        self.inner.downcast_ref::<E>()
+    }
+
+    /// Returns the index of the component this event is scheduled for.
+    /// It is the same for all the events scheduled with the same [`ComponentId`].
+    #[must_use]
+    pub fn component_idx(&self) -> usize {
+        self.component.id
+    }
+
+    /// Checks if the event is scheduled for `component`.
+    pub(crate) fn is_for<E>(&self, component: ComponentId<E>) -> bool {
+        self.component == component.cast()
+    }
+
+    /// Returns the ID of the component this event is scheduled for, regardless of its event type.
+    pub(crate) fn component(&self) -> ComponentId<()> {
         self.component
     }
 
+    /// Returns the time at which the event occurs.
     #[must_use]
-    pub(crate) fn time(&self) -> Duration {
+    pub fn time(&self) -> T {
         self.time.0
     }
 }
 
-impl PartialEq for EventEntry {
+impl<T: SimTime> PartialEq for EventEntry<T> {
     fn eq(&self, other: &Self) -> bool {
         self.time == other.time
+            && self.priority == other.priority
+            && self.sequence == other.sequence
     }
 }
 
-impl Eq for EventEntry {}
+impl<T: SimTime> Eq for EventEntry<T> {}
 
-impl PartialOrd for EventEntry {
+impl<T: SimTime> PartialOrd for EventEntry<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.time.partial_cmp(&other.time)
+        Some(self.cmp(other))
     }
 }
 
-impl Ord for EventEntry {
+/// Entries are ordered such that the _greatest_ one is the next to occur:
+/// first by the (reversed) time, then by the priority, and finally by the (reversed)
+/// insertion sequence, so that events with the same time and priority are processed
+/// in the FIFO order.
+impl<T: SimTime> Ord for EventEntry<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.time.cmp(&other.time)
+        self.time
+            .cmp(&other.time)
+            .then_with(|| self.priority.cmp(&other.priority))
+            .then_with(|| other.sequence.cmp(&self.sequence))
     }
 }
 
 #[derive(Debug)]
 pub struct EventEntryTyped<'e, E: fmt::Debug> {
-    pub time: Duration,
     pub component_id: ComponentId<E>,
-    pub component_idx: usize,
     pub event: &'e E,
 }
 
+/// Error returned when an attempt to schedule an event at an absolute time fails due to
+/// the time being in the past.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ScheduleError<T = Duration> {
+    /// The requested time of the event.
+    pub time: T,
+    /// The simulation time at the moment of the attempt.
+    pub now: T,
+}
+
+impl<T: SimTime> fmt::Display for ScheduleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot schedule event at {:?}, which is before the current time {:?}",
+            self.time, self.now
+        )
+    }
+}
+
+impl<T: SimTime> std::error::Error for ScheduleError<T> {}
+
+/// Opaque handle identifying a scheduled event.
+///
+/// It is returned by [`Scheduler::schedule`] and can be later passed to [`Scheduler::cancel`]
+/// to withdraw the event before it occurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventHandle(usize);
+
 /// This struct exposes only immutable access to the simulation clock.
 /// The clock itself is owned by the scheduler, while others can obtain `ClockRef`
 /// to read the current simulation time.
@@ -97,83 +154,693 @@ pub struct EventEntryTyped<'e, E: fmt::Debug> {
 /// let clock_ref = scheduler.clock();
 /// assert_eq!(clock_ref.time(), scheduler.time());
 /// ```
-pub struct ClockRef {
-    clock: Clock,
+pub struct ClockRef<T = Duration> {
+    clock: Clock<T>,
+}
+
+impl<T> Clone for ClockRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            clock: self.clock.clone(),
+        }
+    }
 }
 
-impl From<Clock> for ClockRef {
-    fn from(clock: Clock) -> Self {
+impl<T: SimTime> From<Clock<T>> for ClockRef<T> {
+    fn from(clock: Clock<T>) -> Self {
         Self { clock }
     }
 }
 
-impl ClockRef {
+impl<T: SimTime> ClockRef<T> {
     /// Return the current simulation time.
     #[must_use]
-    pub fn time(&self) -> Duration {
+    pub fn time(&self) -> T {
         self.clock.get()
     }
 }
 
 /// Scheduler is used to keep the current time and information about the upcoming events.
 ///
+/// # Ordering Guarantee
+///
+/// Events are processed in the order of their time. Events scheduled for the same time
+/// are processed in the order of their priority, the higher the sooner
+/// (see [`Scheduler::schedule_with_priority`]). Finally, events with the same time and priority
+/// are processed in the order in which they were scheduled (FIFO). This order is deterministic
+/// and does not depend on the internal layout of the event queue.
+///
+/// # Simulation Time
+///
+/// The scheduler is generic over the time type `T`, which defaults to [`Duration`].
+/// A scheduler with any other [`SimTime`] can be created with [`Scheduler::new`].
+///
+/// # Event Queue
+///
+/// The scheduler is also generic over the queue `Q` storing the upcoming events, which defaults
+/// to [`BinaryHeapQueue`]. A different [`EventQueue`] implementation can be used by creating
+/// the scheduler with [`Scheduler::with_queue`]. See [`EventQueue`] for the trade-offs between
+/// the provided implementations.
+///
 /// See the [crate-level documentation](index.html) for more information.
-pub struct Scheduler {
-    events: BinaryHeap<EventEntry>,
-    clock: Clock,
+pub struct Scheduler<T = Duration, Q = BinaryHeapQueue<T>> {
+    events: Q,
+    time: T,
+    clock: Clock<T>,
+    pending: HashSet<EventHandle>,
+    periodic: HashMap<EventHandle, PeriodicEvent<T>>,
+    next_sequence: usize,
+    /// Time of the next pending event, kept up to date so that it can be read
+    /// without discarding the cancelled events from the queue.
+    next_time: Option<T>,
+}
+
+/// Determines when a periodic event stops recurring.
+///
+/// See [`Scheduler::schedule_periodic_until`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodicEnd<T = Duration> {
+    /// No event will occur after this time.
+    Time(T),
+    /// The event will occur exactly this many times.
+    Count(usize),
+}
+
+/// Periodic event that has some future occurrences left.
+struct PeriodicEvent<T> {
+    component: ComponentId<()>,
+    next: T,
+    period: T,
+    until: Option<T>,
+    remaining: Option<usize>,
+    event_factory: EventFactory,
 }
 
 impl Default for Scheduler {
     fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cancelled events are removed lazily when they reach the top of the queue.
+/// Once the queue holds this many entries and more than half of them are cancelled,
+/// it is compacted to free the memory.
+const COMPACTION_THRESHOLD: usize = 1024;
+
+// `Default` is implemented only for `Duration` so that the time type can be inferred.
+#[allow(clippy::new_without_default)]
+impl<T: SimTime> Scheduler<T> {
+    /// Creates a new scheduler with the clock set to `T::default()`, which stores
+    /// the upcoming events in a [`BinaryHeapQueue`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_queue(BinaryHeapQueue::default())
+    }
+}
+
+impl<T: SimTime, Q: EventQueue<T>> Scheduler<T, Q> {
+    /// Creates a new scheduler that stores the upcoming events in the given queue.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{CalendarQueue, Scheduler};
+    /// # use std::time::Duration;
+    /// let scheduler: Scheduler<Duration, _> = Scheduler::with_queue(CalendarQueue::default());
+    /// ```
+    #[must_use]
+    pub fn with_queue(queue: Q) -> Self {
         Self {
-            events: BinaryHeap::default(),
-            clock: Rc::new(Cell::new(Duration::default())),
+            events: queue,
+            time: T::default(),
+            clock: Clock::new(T::default()),
+            pending: HashSet::default(),
+            periodic: HashMap::default(),
+            next_sequence: 0,
+            next_time: None,
         }
     }
-}
 
-impl Scheduler {
     /// Schedules `event` to be executed for `component` at `self.time() + time`.
-    pub fn schedule<E: fmt::Debug + 'static>(
+    ///
+    /// Returns a handle that can be used to cancel the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting time is before the current time or is NaN,
+    /// e.g., when `time` is a negative or NaN [`OrderedF64`](crate::OrderedF64).
+    pub fn schedule<E: fmt::Debug + MaybeSend + 'static>(
+        &mut self,
+        time: T,
+        component: ComponentId<E>,
+        event: E,
+    ) -> EventHandle {
+        self.schedule_with_priority(time, component, event, 0)
+    }
+
+    /// Schedules `event` to be executed for `component` at `self.time() + time` with the
+    /// given `priority`.
+    ///
+    /// Among the events occurring at the same time, the ones with higher priority are processed
+    /// first. Events scheduled with [`Scheduler::schedule`] have the priority of 0.
+    ///
+    /// Returns a handle that can be used to cancel the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting time is before the current time or is NaN,
+    /// just like [`Scheduler::schedule`].
+    pub fn schedule_with_priority<E: fmt::Debug + MaybeSend + 'static>(
         &mut self,
-        time: Duration,
+        time: T,
         component: ComponentId<E>,
         event: E,
-    ) {
+        priority: i32,
+    ) -> EventHandle {
         let time = self.time() + time;
-        self.events.push(EventEntry::new(time, component, event));
+        self.push(time, component, event, priority)
+    }
+
+    /// Schedules `event` to be executed for `component` at the absolute time `time`.
+    ///
+    /// Returns a handle that can be used to cancel the event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `time` is earlier than the current simulation time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is NaN.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # struct Shift;
+    /// # impl Component for Shift {
+    /// #     type Event = ();
+    /// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let component = components.add_component(Shift);
+    /// let mut scheduler = Scheduler::default();
+    /// scheduler.schedule(Duration::from_secs(10), component, ());
+    /// let _ = scheduler.pop();
+    /// assert!(scheduler.schedule_at(Duration::from_secs(15), component, ()).is_ok());
+    /// assert!(scheduler.schedule_at(Duration::from_secs(5), component, ()).is_err());
+    /// ```
+    pub fn schedule_at<E: fmt::Debug + MaybeSend + 'static>(
+        &mut self,
+        time: T,
+        component: ComponentId<E>,
+        event: E,
+    ) -> Result<EventHandle, ScheduleError<T>> {
+        let now = self.time();
+        if time < now {
+            return Err(ScheduleError { time, now });
+        }
+        Ok(self.push(time, component, event, 0))
+    }
+
+    /// Pushes a new event occurring at the absolute `time` to the event queue.
+    fn push<E: fmt::Debug + MaybeSend + 'static>(
+        &mut self,
+        time: T,
+        component: ComponentId<E>,
+        event: E,
+        priority: i32,
+    ) -> EventHandle {
+        self.check_time(time);
+        let handle = EventHandle(self.next_sequence);
+        self.pending.insert(handle);
+        self.push_entry(EventEntry {
+            handle,
+            priority,
+            ..EventEntry::new(time, component, event)
+        });
+        handle
+    }
+
+    /// Panics if an event cannot occur at `time`, because it is either in the past or NaN.
+    /// Otherwise, it could move the clock backwards or break the ordering of the events.
+    fn check_time(&self, time: T) {
+        let now = self.time();
+        assert!(
+            time >= now && !time.to_f64().is_nan(),
+            "cannot schedule event at {:?}, which is before the current time {:?} or NaN",
+            time,
+            now
+        );
+    }
+
+    /// Assigns the next insertion sequence number to the entry and pushes it to the event queue.
+    fn push_entry(&mut self, entry: EventEntry<T>) {
+        let time = entry.time();
+        if self.next_time.map_or(true, |next| time < next) {
+            self.next_time = Some(time);
+        }
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.events.push(EventEntry { sequence, ..entry });
     }
 
     /// Schedules `event` to be executed for `component` at `self.time()`.
-    pub fn schedule_now<E: fmt::Debug + 'static>(&mut self, component: ComponentId<E>, event: E) {
-        self.schedule(Duration::default(), component, event);
+    ///
+    /// Returns a handle that can be used to cancel the event.
+    pub fn schedule_now<E: fmt::Debug + MaybeSend + 'static>(
+        &mut self,
+        component: ComponentId<E>,
+        event: E,
+    ) -> EventHandle {
+        self.schedule(T::default(), component, event)
+    }
+
+    /// Schedules a periodic event for `component`. The first occurrence is at `self.time() + start`,
+    /// and each next one is `period` after the previous one. The event value for each occurrence
+    /// is produced by `event_factory`.
+    ///
+    /// The occurrences are scheduled lazily: only the next one is stored in the queue
+    /// at any given time. Each occurrence is scheduled at the moment when the previous one
+    /// is popped, which determines its FIFO order among other events with the same time.
+    /// The events recur indefinitely until cancelled with [`Scheduler::cancel`], which cancels
+    /// all future occurrences.
+    /// See [`Scheduler::schedule_periodic_until`] for a bounded alternative.
+    ///
+    /// Note that the period should be greater than zero, or else the simulation time will
+    /// never advance past the first occurrence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time of the first occurrence is before the current time or is NaN,
+    /// just like [`Scheduler::schedule`], or if `period` is negative or NaN.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # struct Producer;
+    /// # impl Component for Producer {
+    /// #     type Event = ();
+    /// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let component = components.add_component(Producer);
+    /// let mut scheduler = Scheduler::default();
+    /// let handle = scheduler.schedule_periodic(
+    ///     Duration::from_secs(1),
+    ///     Duration::from_secs(2),
+    ///     component,
+    ///     || (),
+    /// );
+    /// scheduler.pop();
+    /// assert_eq!(scheduler.time(), Duration::from_secs(1));
+    /// scheduler.pop();
+    /// assert_eq!(scheduler.time(), Duration::from_secs(3));
+    /// scheduler.pop();
+    /// assert_eq!(scheduler.time(), Duration::from_secs(5));
+    /// scheduler.cancel(handle);
+    /// assert!(scheduler.pop().is_none());
+    /// ```
+    pub fn schedule_periodic<E, F>(
+        &mut self,
+        start: T,
+        period: T,
+        component: ComponentId<E>,
+        event_factory: F,
+    ) -> EventHandle
+    where
+        E: fmt::Debug + MaybeSend + 'static,
+        F: FnMut() -> E + MaybeSend + 'static,
+    {
+        self.push_periodic(start, period, None, None, component, event_factory)
+    }
+
+    /// Schedules a periodic event for `component` just like [`Scheduler::schedule_periodic`],
+    /// but the events stop recurring once the `end` condition is reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`Scheduler::schedule_periodic`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, PeriodicEnd, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # struct Producer;
+    /// # impl Component for Producer {
+    /// #     type Event = ();
+    /// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let component = components.add_component(Producer);
+    /// let mut scheduler = Scheduler::default();
+    /// let period = Duration::from_secs(1);
+    /// scheduler.schedule_periodic_until(period, period, PeriodicEnd::Count(2), component, || ());
+    /// scheduler.pop();
+    /// assert_eq!(scheduler.time(), Duration::from_secs(1));
+    /// scheduler.pop();
+    /// assert_eq!(scheduler.time(), Duration::from_secs(2));
+    /// assert!(scheduler.pop().is_none());
+    /// ```
+    pub fn schedule_periodic_until<E, F>(
+        &mut self,
+        start: T,
+        period: T,
+        end: PeriodicEnd<T>,
+        component: ComponentId<E>,
+        event_factory: F,
+    ) -> EventHandle
+    where
+        E: fmt::Debug + MaybeSend + 'static,
+        F: FnMut() -> E + MaybeSend + 'static,
+    {
+        match end {
+            PeriodicEnd::Time(time) => {
+                self.push_periodic(start, period, Some(time), None, component, event_factory)
+            }
+            PeriodicEnd::Count(count) => {
+                self.push_periodic(start, period, None, Some(count), component, event_factory)
+            }
+        }
+    }
+
+    fn push_periodic<E, F>(
+        &mut self,
+        start: T,
+        period: T,
+        until: Option<T>,
+        remaining: Option<usize>,
+        component: ComponentId<E>,
+        mut event_factory: F,
+    ) -> EventHandle
+    where
+        E: fmt::Debug + MaybeSend + 'static,
+        F: FnMut() -> E + MaybeSend + 'static,
+    {
+        let next = self.time() + start;
+        self.check_time(next);
+        assert!(
+            next + period >= next && !period.to_f64().is_nan(),
+            "period of a periodic event must not be negative or NaN, got {:?}",
+            period
+        );
+        let handle = EventHandle(self.next_sequence);
+        self.next_sequence += 1;
+        self.pending.insert(handle);
+        self.periodic.insert(
+            handle,
+            PeriodicEvent {
+                component: component.cast(),
+                next,
+                period,
+                until,
+                remaining,
+                event_factory: Box::new(move || Box::new(event_factory())),
+            },
+        );
+        self.push_next_occurrence(handle);
+        handle
+    }
+
+    /// Pushes the next occurrence of a periodic event to the queue, or removes the periodic
+    /// event if it has reached its end.
+    fn push_next_occurrence(&mut self, handle: EventHandle) {
+        let periodic = self
+            .periodic
+            .get_mut(&handle)
+            .expect("Periodic event must exist.");
+        let time = periodic.next;
+        let finished =
+            periodic.remaining == Some(0) || periodic.until.is_some_and(|until| time > until);
+        if finished {
+            self.periodic.remove(&handle);
+            self.pending.remove(&handle);
+            return;
+        }
+        periodic.next = time + periodic.period;
+        periodic.remaining = periodic.remaining.map(|remaining| remaining - 1);
+
+        let entry = EventEntry {
+            time: Reverse(time),
+            component: periodic.component,
+            handle,
+            priority: 0,
+            sequence: 0,
+            inner: (periodic.event_factory)(),
+        };
+        self.push_entry(entry);
+    }
+
+    /// Cancels a pending event. Returns `true` if the event was pending, and `false`
+    /// if it has already occurred or has been cancelled before.
+    ///
+    /// A cancelled event will never be returned by [`Scheduler::pop`].
+    /// Cancelling a periodic event cancels all its future occurrences.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # struct Client;
+    /// # impl Component for Client {
+    /// #     type Event = ();
+    /// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let component = components.add_component(Client);
+    /// let mut scheduler = Scheduler::default();
+    /// let timeout = scheduler.schedule(Duration::from_secs(5), component, ());
+    /// assert!(scheduler.is_pending(timeout));
+    /// assert!(scheduler.cancel(timeout));
+    /// assert!(!scheduler.is_pending(timeout));
+    /// assert!(!scheduler.cancel(timeout));
+    /// assert!(scheduler.pop().is_none());
+    /// ```
+    pub fn cancel(&mut self, handle: EventHandle) -> bool {
+        let cancelled = self.pending.remove(&handle);
+        self.periodic.remove(&handle);
+        if cancelled
+            && self.events.len() >= COMPACTION_THRESHOLD
+            && self.events.len() > 2 * self.pending.len()
+        {
+            let pending = &self.pending;
+            self.events
+                .retain(&mut |event| pending.contains(&event.handle));
+        }
+        if cancelled {
+            self.update_next_time();
+        }
+        cancelled
+    }
+
+    /// Checks if the event is still waiting to occur, i.e., it has been neither
+    /// processed nor cancelled. A periodic event is pending as long as it has any future
+    /// occurrences.
+    #[must_use]
+    pub fn is_pending(&self, handle: EventHandle) -> bool {
+        self.pending.contains(&handle)
     }
 
     /// Returns the current simulation time.
     #[must_use]
-    pub fn time(&self) -> Duration {
-        self.clock.get()
+    pub fn time(&self) -> T {
+        self.time
     }
 
     /// Returns a structure with immutable access to the simulation time.
     #[must_use]
-    pub fn clock(&self) -> ClockRef {
+    pub fn clock(&self) -> ClockRef<T> {
+        // The shared clock is not updated while there are no references to it.
+        self.clock.set(self.time);
         ClockRef {
-            clock: Rc::clone(&self.clock),
+            clock: self.clock.clone(),
         }
     }
 
+    /// Returns the number of pending events. A periodic event counts as one event.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns `true` if there are no pending events.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the time of the next event or `None` if none are left.
+    ///
+    /// It takes constant time, since the time of the next event is updated whenever
+    /// an event is scheduled, cancelled, or popped.
+    #[must_use]
+    pub fn next_event_time(&self) -> Option<T> {
+        self.next_time
+    }
+
+    /// Returns an iterator over `(time, component_idx)` pairs of the pending events,
+    /// in an arbitrary order. For periodic events, only the next occurrence is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # struct Server;
+    /// # impl Component for Server {
+    /// #     type Event = ();
+    /// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let component = components.add_component(Server);
+    /// let mut scheduler = Scheduler::default();
+    /// scheduler.schedule(Duration::from_secs(2), component, ());
+    /// scheduler.schedule(Duration::from_secs(1), component, ());
+    /// let mut times: Vec<_> = scheduler.iter().map(|(time, _)| time).collect();
+    /// times.sort();
+    /// assert_eq!(times, vec![Duration::from_secs(1), Duration::from_secs(2)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (T, usize)> + '_ {
+        self.entries()
+            .map(|entry| (entry.time(), entry.component_idx()))
+    }
+
+    /// Returns the number of pending events scheduled for the given component.
+    ///
+    /// Note that it iterates over all pending events, so it takes time linear in their number
+    /// and should be used with care, e.g., not when processing every event.
+    #[must_use]
+    pub fn pending_count<E: fmt::Debug + 'static>(&self, component: ComponentId<E>) -> usize {
+        self.entries()
+            .filter(|entry| entry.is_for(component))
+            .count()
+    }
+
+    /// Returns an iterator over the pending events scheduled for the given component,
+    /// in an arbitrary order. Each item is a pair of the event time and a reference to the event.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # #[derive(Debug, PartialEq)]
+    /// # enum Event { Timeout }
+    /// # struct Client;
+    /// # impl Component for Client {
+    /// #     type Event = Event;
+    /// #     fn process_event(&self, _: ComponentId<Event>, _: &Event, _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let component = components.add_component(Client);
+    /// let mut scheduler = Scheduler::default();
+    /// scheduler.schedule(Duration::from_secs(5), component, Event::Timeout);
+    /// assert_eq!(
+    ///     scheduler.pending_for(component).collect::<Vec<_>>(),
+    ///     vec![(Duration::from_secs(5), &Event::Timeout)]
+    /// );
+    /// ```
+    pub fn pending_for<E: fmt::Debug + 'static>(
+        &self,
+        component: ComponentId<E>,
+    ) -> impl Iterator<Item = (T, &E)> + '_ {
+        self.entries()
+            .filter(move |entry| entry.is_for(component))
+            .filter_map(|entry| entry.downcast_ref::<E>().map(|event| (entry.time(), event)))
+    }
+
+    /// Retains only the pending events for which `predicate` returns `true`.
+    /// The removed events are cancelled, as if [`Scheduler::cancel`] was called with their handles.
+    /// For periodic events, the predicate is called for the next occurrence, and if it returns
+    /// `false`, all future occurrences are cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simrs::{Component, ComponentId, Components, Scheduler, State};
+    /// # use std::time::Duration;
+    /// # struct Machine;
+    /// # impl Component for Machine {
+    /// #     type Event = ();
+    /// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+    /// # }
+    /// # let mut components = Components::default();
+    /// # let failed = components.add_component(Machine);
+    /// # let working = components.add_component(Machine);
+    /// let mut scheduler = Scheduler::default();
+    /// scheduler.schedule(Duration::from_secs(1), failed, ());
+    /// scheduler.schedule(Duration::from_secs(2), working, ());
+    /// scheduler.retain(|entry| entry.component_idx() != failed.idx());
+    /// assert_eq!(scheduler.pending_count(failed), 0);
+    /// assert_eq!(scheduler.pending_count(working), 1);
+    /// ```
+    pub fn retain<F: FnMut(&EventEntry<T>) -> bool>(&mut self, mut predicate: F) {
+        let pending = &mut self.pending;
+        let periodic = &mut self.periodic;
+        self.events.retain(&mut |entry| {
+            if !pending.contains(&entry.handle) {
+                return false;
+            }
+            let keep = predicate(entry);
+            if !keep {
+                pending.remove(&entry.handle);
+                periodic.remove(&entry.handle);
+            }
+            keep
+        });
+        self.update_next_time();
+    }
+
+    /// Iterates over the entries that have not been cancelled.
+    fn entries(&self) -> impl Iterator<Item = &EventEntry<T>> + '_ {
+        self.events
+            .iter()
+            .filter(move |entry| self.pending.contains(&entry.handle))
+    }
+
     /// Returns a reference to the next scheduled event or `None` if none are left.
-    pub fn peek(&mut self) -> Option<&EventEntry> {
+    pub fn peek(&mut self) -> Option<&EventEntry<T>> {
+        self.discard_cancelled();
         self.events.peek()
     }
 
     /// Removes and returns the next scheduled event or `None` if none are left.
-    pub fn pop(&mut self) -> Option<EventEntry> {
-        self.events.pop().map(|event| {
-            self.clock.replace(event.time.0);
-            event
-        })
+    pub fn pop(&mut self) -> Option<EventEntry<T>> {
+        self.discard_cancelled();
+        let event = self.events.pop()?;
+        self.time = event.time.0;
+        if self.clock.is_shared() {
+            self.clock.set(self.time);
+        }
+        if self.periodic.contains_key(&event.handle) {
+            self.push_next_occurrence(event.handle);
+        } else {
+            self.pending.remove(&event.handle);
+        }
+        self.update_next_time();
+        Some(event)
+    }
+
+    /// Discards the cancelled events from the top of the queue, and records the time
+    /// of the next pending one.
+    fn update_next_time(&mut self) {
+        self.discard_cancelled();
+        self.next_time = self.events.peek().map(EventEntry::time);
+    }
+
+    /// Removes cancelled events from the top of the queue.
+    fn discard_cancelled(&mut self) {
+        while let Some(event) = self.events.peek() {
+            if self.pending.contains(&event.handle) {
+                break;
+            }
+            self.events.pop();
+        }
     }
 }
 
@@ -184,7 +851,7 @@ mod test {
     #[test]
     fn test_clock_ref() {
         let time = Duration::from_secs(1);
-        let clock = Clock::new(Cell::new(time));
+        let clock = Clock::new(time);
         let clock_ref = ClockRef::from(clock);
         assert_eq!(clock_ref.time(), time);
     }
@@ -193,7 +860,10 @@ mod test {
     fn test_event_entry_downcast() {
         let entry = EventEntry {
             time: Reverse(Duration::from_secs(1)),
-            component: 2,
+            component: ComponentId::new(0, 2),
+            handle: EventHandle(0),
+            priority: 0,
+            sequence: 0,
             inner: Box::new(String::from("inner")),
         };
         assert!(entry.downcast::<String>().is_some());
@@ -204,7 +874,10 @@ mod test {
     fn test_event_entry_cmp() {
         let make_entry = || EventEntry {
             time: Reverse(Duration::from_secs(1)),
-            component: 2,
+            component: ComponentId::new(0, 2),
+            handle: EventHandle(0),
+            priority: 0,
+            sequence: 0,
             inner: Box::new(String::from("inner")),
         };
         assert_eq!(
@@ -239,6 +912,70 @@ mod test {
             }),
             Ordering::Less
         );
+        assert_eq!(
+            EventEntry {
+                sequence: 0,
+                ..make_entry()
+            }
+            .cmp(&EventEntry {
+                sequence: 1,
+                ..make_entry()
+            }),
+            Ordering::Greater
+        );
+        assert_eq!(
+            EventEntry {
+                priority: 1,
+                sequence: 1,
+                ..make_entry()
+            }
+            .cmp(&EventEntry {
+                priority: 0,
+                sequence: 0,
+                ..make_entry()
+            }),
+            Ordering::Greater
+        );
+        assert_eq!(
+            EventEntry {
+                time: Reverse(Duration::from_secs(2)),
+                priority: 1,
+                ..make_entry()
+            }
+            .cmp(&EventEntry {
+                priority: 0,
+                ..make_entry()
+            }),
+            Ordering::Less
+        );
+        assert_ne!(
+            EventEntry {
+                sequence: 0,
+                ..make_entry()
+            },
+            EventEntry {
+                sequence: 1,
+                ..make_entry()
+            }
+        );
+    }
+
+    #[test]
+    fn test_shared_clock() {
+        let mut scheduler = Scheduler::<u64>::new();
+        let component = ComponentId::<EventA>::new(0, 0);
+        scheduler.schedule(1, component, EventA);
+        scheduler.schedule(2, component, EventA);
+        scheduler.schedule(3, component, EventA);
+        scheduler.pop();
+        let clock = scheduler.clock();
+        assert_eq!(clock.time(), 1);
+        scheduler.pop();
+        assert_eq!(clock.time(), 2);
+        drop(clock);
+        scheduler.pop();
+        assert_eq!(scheduler.time(), 3);
+        assert_eq!(scheduler.clock().time(), 3);
     }
 
     #[derive(Debug, Clone, Eq, PartialEq)]
@@ -253,8 +990,8 @@ mod test {
         assert_eq!(scheduler.clock().time(), Duration::new(0, 0));
         assert!(scheduler.events.is_empty());
 
-        let component_a = ComponentId::<EventA>::new(0);
-        let component_b = ComponentId::<EventB>::new(1);
+        let component_a = ComponentId::<EventA>::new(0, 0);
+        let component_b = ComponentId::<EventB>::new(0, 1);
 
         scheduler.schedule(Duration::from_secs(1), component_a, EventA);
         scheduler.schedule_now(component_b, EventB);
@@ -263,18 +1000,18 @@ mod test {
         assert_eq!(scheduler.time(), Duration::from_secs(0));
 
         let entry = scheduler.pop().unwrap();
+        assert_eq!(entry.time(), Duration::from_secs(0));
+        assert_eq!(entry.component_idx(), 1);
         let entry = entry.downcast::<EventB>().unwrap();
-        assert_eq!(entry.time, Duration::from_secs(0));
-        assert_eq!(entry.component_idx, 1);
         assert_eq!(entry.component_id, component_b);
         assert_eq!(entry.event, &EventB);
 
         assert_eq!(scheduler.time(), Duration::from_secs(0));
 
         let entry = scheduler.pop().unwrap();
+        assert_eq!(entry.time(), Duration::from_secs(1));
+        assert_eq!(entry.component_idx(), 0);
         let entry = entry.downcast::<EventA>().unwrap();
-        assert_eq!(entry.time, Duration::from_secs(1));
-        assert_eq!(entry.component_idx, 0);
         assert_eq!(entry.component_id, component_a);
         assert_eq!(entry.event, &EventA);
 
@@ -282,9 +1019,9 @@ mod test {
         assert_eq!(scheduler.clock().time(), Duration::from_secs(1));
 
         let entry = scheduler.pop().unwrap();
+        assert_eq!(entry.time(), Duration::from_secs(2));
+        assert_eq!(entry.component_idx(), 1);
         let entry = entry.downcast::<EventB>().unwrap();
-        assert_eq!(entry.time, Duration::from_secs(2));
-        assert_eq!(entry.component_idx, 1);
         assert_eq!(entry.component_id, component_b);
         assert_eq!(entry.event, &EventB);
 
@@ -292,4 +1029,402 @@ mod test {
 
         assert!(scheduler.pop().is_none());
     }
+
+    #[test]
+    fn test_cancel() {
+        let mut scheduler = Scheduler::default();
+        let component = ComponentId::<EventA>::new(0, 0);
+
+        let first = scheduler.schedule(Duration::from_secs(1), component, EventA);
+        let second = scheduler.schedule(Duration::from_secs(2), component, EventA);
+        let third = scheduler.schedule(Duration::from_secs(3), component, EventA);
+        assert!(scheduler.is_pending(first));
+        assert!(scheduler.is_pending(second));
+        assert!(scheduler.is_pending(third));
+
+        assert!(scheduler.cancel(first));
+        assert!(!scheduler.cancel(first));
+        assert!(!scheduler.is_pending(first));
+        assert!(scheduler.cancel(third));
+
+        assert_eq!(scheduler.peek().unwrap().time(), Duration::from_secs(2));
+        assert_eq!(scheduler.pop().unwrap().time(), Duration::from_secs(2));
+        assert!(!scheduler.is_pending(second));
+        assert!(!scheduler.cancel(second));
+        assert_eq!(scheduler.time(), Duration::from_secs(2));
+        assert!(scheduler.pop().is_none());
+    }
+
+    #[test]
+    fn test_cancel_compacts_queue() {
+        let mut scheduler = Scheduler::default();
+        let component = ComponentId::<EventA>::new(0, 0);
+        let handles: Vec<_> = (0..2 * COMPACTION_THRESHOLD as u64)
+            .map(|secs| scheduler.schedule(Duration::from_secs(secs), component, EventA))
+            .collect();
+        for handle in handles.iter().skip(1) {
+            assert!(scheduler.cancel(*handle));
+        }
+        assert!(scheduler.events.len() < COMPACTION_THRESHOLD);
+        assert!(scheduler.is_pending(handles[0]));
+        assert_eq!(scheduler.pop().unwrap().time(), Duration::from_secs(0));
+        assert!(scheduler.pop().is_none());
+    }
+
+    fn pop_events<Q: EventQueue>(scheduler: &mut Scheduler<Duration, Q>) -> Vec<(Duration, usize)> {
+        std::iter::from_fn(|| scheduler.pop())
+            .map(|entry| (entry.time(), *entry.downcast::<usize>().unwrap().event))
+            .collect()
+    }
+
+    #[test]
+    fn test_simultaneous_events_fifo() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let run = || {
+            let mut scheduler = Scheduler::default();
+            for event in 0..100 {
+                scheduler.schedule(Duration::from_secs((event % 3) as u64), component, event);
+            }
+            pop_events(&mut scheduler)
+        };
+        let events = run();
+        let expected: Vec<_> = (0..3)
+            .flat_map(|secs| {
+                (0..100)
+                    .filter(move |event| event % 3 == secs)
+                    .map(move |event| (Duration::from_secs(secs as u64), event))
+            })
+            .collect();
+        assert_eq!(events, expected);
+        assert_eq!(run(), events);
+    }
+
+    #[test]
+    fn test_schedule_now_after_pop_fifo() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Duration::from_secs(1), component, 0);
+        scheduler.schedule(Duration::from_secs(1), component, 1);
+        assert_eq!(scheduler.pop().unwrap().time(), Duration::from_secs(1));
+        scheduler.schedule_now(component, 2);
+        scheduler.schedule_now(component, 3);
+        assert_eq!(
+            pop_events(&mut scheduler),
+            vec![
+                (Duration::from_secs(1), 1),
+                (Duration::from_secs(1), 2),
+                (Duration::from_secs(1), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_priorities() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Duration::from_secs(1), component, 0);
+        scheduler.schedule_with_priority(Duration::from_secs(1), component, 1, -1);
+        scheduler.schedule_with_priority(Duration::from_secs(1), component, 2, 1);
+        scheduler.schedule_with_priority(Duration::from_secs(0), component, 3, -5);
+        scheduler.schedule_with_priority(Duration::from_secs(1), component, 4, 1);
+        scheduler.schedule(Duration::from_secs(1), component, 5);
+        assert_eq!(
+            pop_events(&mut scheduler),
+            vec![
+                (Duration::from_secs(0), 3),
+                (Duration::from_secs(1), 2),
+                (Duration::from_secs(1), 4),
+                (Duration::from_secs(1), 0),
+                (Duration::from_secs(1), 5),
+                (Duration::from_secs(1), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schedule_at() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::default();
+        assert!(scheduler
+            .schedule_at(Duration::from_secs(2), component, 0)
+            .is_ok());
+        scheduler.schedule(Duration::from_secs(1), component, 1);
+        assert_eq!(scheduler.pop().unwrap().time(), Duration::from_secs(1));
+
+        let err = scheduler
+            .schedule_at(Duration::from_millis(500), component, 2)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ScheduleError {
+                time: Duration::from_millis(500),
+                now: Duration::from_secs(1),
+            }
+        );
+        assert_eq!(
+            &format!("{err}"),
+            "cannot schedule event at 500ms, which is before the current time 1s"
+        );
+
+        let now = scheduler
+            .schedule_at(Duration::from_secs(1), component, 3)
+            .unwrap();
+        assert!(scheduler.is_pending(now));
+        assert_eq!(
+            pop_events(&mut scheduler),
+            vec![(Duration::from_secs(1), 3), (Duration::from_secs(2), 0)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot schedule event")]
+    fn test_schedule_negative_delay() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::<crate::OrderedF64>::new();
+        scheduler.schedule(crate::OrderedF64(1.0), component, 0);
+        scheduler.pop();
+        scheduler.schedule(crate::OrderedF64(-0.5), component, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot schedule event")]
+    fn test_schedule_nan_delay() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::<crate::OrderedF64>::new();
+        scheduler.schedule(crate::OrderedF64(f64::NAN), component, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be negative or NaN")]
+    fn test_periodic_negative_period() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::<crate::OrderedF64>::new();
+        scheduler.schedule_periodic(
+            crate::OrderedF64(1.0),
+            crate::OrderedF64(-1.0),
+            component,
+            || 0,
+        );
+    }
+
+    #[test]
+    fn test_periodic() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::default();
+        let mut counter = 0;
+        let handle = scheduler.schedule_periodic(
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            component,
+            move || {
+                counter += 1;
+                counter
+            },
+        );
+        scheduler.schedule(Duration::from_secs(3), component, 0);
+        assert!(scheduler.is_pending(handle));
+        assert_eq!(scheduler.events.len(), 2);
+        assert_eq!(
+            (0..4)
+                .map(|_| {
+                    let entry = scheduler.pop().unwrap();
+                    (entry.time(), *entry.downcast::<usize>().unwrap().event)
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (Duration::from_secs(1), 1),
+                (Duration::from_secs(3), 0),
+                (Duration::from_secs(3), 2),
+                (Duration::from_secs(5), 3),
+            ]
+        );
+        assert!(scheduler.is_pending(handle));
+        assert_eq!(scheduler.events.len(), 1);
+        assert!(scheduler.cancel(handle));
+        assert!(!scheduler.is_pending(handle));
+        assert!(scheduler.pop().is_none());
+    }
+
+    #[test]
+    fn test_periodic_until() {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut scheduler = Scheduler::default();
+        let count = scheduler.schedule_periodic_until(
+            Duration::default(),
+            Duration::from_secs(2),
+            PeriodicEnd::Count(3),
+            component,
+            || 0,
+        );
+        let time = scheduler.schedule_periodic_until(
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            PeriodicEnd::Time(Duration::from_secs(5)),
+            component,
+            || 1,
+        );
+        let empty = scheduler.schedule_periodic_until(
+            Duration::default(),
+            Duration::from_secs(2),
+            PeriodicEnd::Count(0),
+            component,
+            || 2,
+        );
+        assert!(scheduler.is_pending(count));
+        assert!(scheduler.is_pending(time));
+        assert!(!scheduler.is_pending(empty));
+        assert_ne!(empty, scheduler.schedule_now(component, 3));
+        assert_eq!(
+            pop_events(&mut scheduler),
+            vec![
+                (Duration::from_secs(0), 0),
+                (Duration::from_secs(0), 3),
+                (Duration::from_secs(1), 1),
+                (Duration::from_secs(2), 0),
+                (Duration::from_secs(3), 1),
+                (Duration::from_secs(4), 0),
+                (Duration::from_secs(5), 1),
+            ]
+        );
+        assert!(!scheduler.is_pending(count));
+        assert!(!scheduler.is_pending(time));
+    }
+
+    fn schedule_and_cancel<Q: EventQueue>(
+        mut scheduler: Scheduler<Duration, Q>,
+    ) -> Vec<(Duration, usize)> {
+        let component = ComponentId::<usize>::new(0, 0);
+        let mut cancelled = Vec::new();
+        for event in 0..100 {
+            let handle =
+                scheduler.schedule(Duration::from_secs((event % 7) as u64), component, event);
+            if event % 3 == 0 {
+                cancelled.push(handle);
+            }
+        }
+        for handle in cancelled {
+            scheduler.cancel(handle);
+        }
+        pop_events(&mut scheduler)
+    }
+
+    #[test]
+    fn test_event_queues() {
+        let expected = schedule_and_cancel(Scheduler::default());
+        assert_eq!(expected.len(), 66);
+        assert_eq!(
+            schedule_and_cancel(Scheduler::with_queue(crate::CalendarQueue::default())),
+            expected
+        );
+        assert_eq!(
+            schedule_and_cancel(Scheduler::with_queue(crate::PairingHeap::default())),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_introspection() {
+        let component_a = ComponentId::<EventA>::new(0, 0);
+        let component_b = ComponentId::<EventB>::new(0, 1);
+        let mut scheduler = Scheduler::default();
+        assert_eq!(scheduler.len(), 0);
+        assert!(scheduler.is_empty());
+        assert_eq!(scheduler.next_event_time(), None);
+        assert_eq!(scheduler.iter().count(), 0);
+
+        let cancelled = scheduler.schedule(Duration::from_secs(1), component_a, EventA);
+        scheduler.schedule(Duration::from_secs(3), component_a, EventA);
+        scheduler.schedule(Duration::from_secs(2), component_b, EventB);
+        scheduler.schedule_periodic(
+            Duration::from_secs(4),
+            Duration::from_secs(1),
+            component_b,
+            || EventB,
+        );
+        assert_eq!(scheduler.len(), 4);
+        assert_eq!(scheduler.pending_count(component_a), 2);
+        assert_eq!(scheduler.pending_count(component_b), 2);
+
+        scheduler.cancel(cancelled);
+        assert_eq!(scheduler.len(), 3);
+        assert!(!scheduler.is_empty());
+        assert_eq!(scheduler.pending_count(component_a), 1);
+        let mut pending: Vec<_> = scheduler.iter().collect();
+        pending.sort();
+        assert_eq!(
+            pending,
+            vec![
+                (Duration::from_secs(2), 1),
+                (Duration::from_secs(3), 0),
+                (Duration::from_secs(4), 1),
+            ]
+        );
+        assert_eq!(scheduler.next_event_time(), Some(Duration::from_secs(2)));
+
+        let entry = scheduler.pop().unwrap();
+        assert_eq!(entry.time(), Duration::from_secs(2));
+        assert_eq!(entry.component_idx(), 1);
+        assert_eq!(scheduler.len(), 2);
+        let shared = &scheduler;
+        assert_eq!(shared.next_event_time(), Some(Duration::from_secs(3)));
+
+        scheduler.retain(|entry| entry.component_idx() != 0);
+        assert_eq!(scheduler.next_event_time(), Some(Duration::from_secs(4)));
+        scheduler.schedule(Duration::from_secs(1), component_a, EventA);
+        assert_eq!(scheduler.next_event_time(), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_downcast_ref() {
+        let entry = EventEntry::new(
+            Duration::default(),
+            ComponentId::<usize>::new(0, 0),
+            7_usize,
+        );
+        assert_eq!(entry.downcast_ref::<usize>(), Some(&7));
+        assert_eq!(entry.downcast_ref::<EventA>(), None);
+    }
+
+    #[test]
+    fn test_pending_for_and_retain() {
+        let component_a = ComponentId::<usize>::new(0, 0);
+        let component_b = ComponentId::<usize>::new(0, 1);
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Duration::from_secs(1), component_a, 1);
+        let cancelled = scheduler.schedule(Duration::from_secs(2), component_a, 2);
+        scheduler.schedule(Duration::from_secs(3), component_b, 3);
+        let periodic = scheduler.schedule_periodic(
+            Duration::from_secs(4),
+            Duration::from_secs(1),
+            component_a,
+            || 4,
+        );
+        scheduler.cancel(cancelled);
+
+        let mut pending: Vec<_> = scheduler.pending_for(component_a).collect();
+        pending.sort();
+        assert_eq!(
+            pending,
+            vec![(Duration::from_secs(1), &1), (Duration::from_secs(4), &4)]
+        );
+        assert_eq!(
+            scheduler.pending_for(component_b).collect::<Vec<_>>(),
+            vec![(Duration::from_secs(3), &3)]
+        );
+
+        let mut visited = Vec::new();
+        scheduler.retain(|entry| {
+            let event = *entry.downcast_ref::<usize>().unwrap();
+            visited.push(event);
+            event % 2 == 1
+        });
+        visited.sort_unstable();
+        assert_eq!(visited, vec![1, 3, 4]);
+        assert!(!scheduler.is_pending(periodic));
+        assert_eq!(scheduler.len(), 2);
+        assert_eq!(
+            pop_events(&mut scheduler),
+            vec![(Duration::from_secs(1), 1), (Duration::from_secs(3), 3)]
+        );
+    }
 }
@@ -1,68 +1,225 @@
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use super::{queue::PushError, Key, Queue, QueueId};
+use super::rng::{RngStream, StreamKey};
+use super::stats::Statistic;
+use super::sync::AnyBox;
+use super::{queue::PushError, Key, MaybeSend, Queue, QueueId, SimError};
 
 /// State of a simulation holding all queues and arbitrary values in a store value.
-#[derive(Default)]
+///
+/// Keys and queue IDs are allocated by the state itself, and they are tagged with the ID space
+/// of the state, which is shared with the other parts of the same [`Simulation`](crate::Simulation).
+/// Keys and IDs issued by another state are never resolved to the values of this one.
+///
+/// The state also owns the random number streams of the simulation, see [`State::rng_stream`].
 pub struct State {
-    store: HashMap<usize, Box<dyn Any>>,
-    queues: HashMap<usize, Box<dyn Any>>,
-    next_id: usize,
+    tag: usize,
+    store: HashMap<usize, AnyBox>,
+    queues: HashMap<usize, AnyBox>,
+    next_key_id: usize,
+    next_queue_id: usize,
+    seed: u64,
+    streams: HashMap<StreamKey, RngStream>,
+    statistics: BTreeMap<String, RegisteredStatistic>,
+}
+
+/// Statistic registered in the value store under a name, see [`State::register`].
+struct RegisteredStatistic {
+    id: usize,
+    summary: fn(&dyn Any) -> Vec<(String, f64)>,
+}
+
+fn summary<S: Statistic>(value: &dyn Any) -> Vec<(String, f64)> {
+    value
+        .downcast_ref::<S>()
+        .expect("Ensured by the registration.")
+        .summary()
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
 }
 
 #[allow(clippy::len_without_is_empty)]
 impl State {
+    /// Creates an empty state issuing keys with the given tag, whose random number streams
+    /// are derived from `seed`.
+    pub(crate) fn with_tag_and_seed(tag: usize, seed: u64) -> Self {
+        Self {
+            tag,
+            store: HashMap::new(),
+            queues: HashMap::new(),
+            next_key_id: 0,
+            next_queue_id: 0,
+            seed,
+            streams: HashMap::new(),
+            statistics: BTreeMap::new(),
+        }
+    }
+
+    /// Creates an empty state whose random number streams are derived from `seed`.
+    /// A state created with [`State::default`] uses the seed `0`.
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_tag_and_seed(super::generate_tag(), seed)
+    }
+
+    /// Returns the seed from which the random number streams are derived.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the random number stream identified by `key`, which is either a name or
+    /// a [`ComponentId`](crate::ComponentId).
+    ///
+    /// Each stream is seeded only from the seed of the state and its own key, and it is created
+    /// lazily on first access. Therefore, streams are reproducible between runs with the same seed,
+    /// and adding a new stream (e.g., for a new component) does not perturb the numbers drawn from
+    /// the existing ones. This makes it possible to use common random numbers when comparing
+    /// different scenarios.
+    ///
+    /// Note that component streams are identified by the index of the component, which
+    /// depends on the order in which the components are added. Use named streams if the
+    /// order may differ between scenarios.
+    ///
+    /// ```
+    /// # use simrs::State;
+    /// use simrs::rand_core::RngCore;
+    /// let mut state = State::with_seed(7);
+    /// let mut other = State::with_seed(7);
+    /// let x = state.rng_stream("arrivals").next_u64();
+    /// let _ = other.rng_stream("service").next_u64();
+    /// assert_eq!(other.rng_stream("arrivals").next_u64(), x);
+    /// ```
+    pub fn rng_stream<K: Into<StreamKey>>(&mut self, key: K) -> &mut RngStream {
+        let seed = self.seed;
+        self.streams
+            .entry(key.into())
+            .or_insert_with_key(|key| RngStream::new(seed, key))
+    }
+
     /// Inserts an arbitrary value to the value store. Learn more in the documentation for [`Key`].
     #[must_use = "Discarding key results in leaking inserted value"]
-    pub fn insert<V: 'static>(&mut self, value: V) -> Key<V> {
-        let id = super::generate_next_id();
+    pub fn insert<V: MaybeSend + 'static>(&mut self, value: V) -> Key<V> {
+        let id = self.next_key_id;
+        self.next_key_id += 1;
         self.store.insert(id, Box::new(value));
-        Key::new(id)
+        Key::new(self.tag, id)
     }
 
     /// Removes a value of type `V` from the value store. Learn more in the documentation for [`Key`].
+    ///
+    /// Returns `None` if the value has been already removed, or if the key was issued
+    /// by another state.
     pub fn remove<V: 'static>(&mut self, key: Key<V>) -> Option<V> {
+        if key.tag != self.tag {
+            return None;
+        }
         self.store
             .remove(&key.id)
-            .map(|v| *v.downcast::<V>().expect("Ensured by the key type."))
+            .and_then(|v| v.downcast::<V>().ok())
+            .map(|v| *v)
     }
 
     /// Gets a immutable reference to a value of a type `V` from the value store.
     /// Learn more in the documentation for [`Key`].
+    ///
+    /// Returns `None` if the value has been removed, or if the key was issued by another state.
     #[must_use]
     pub fn get<V: 'static>(&self, key: Key<V>) -> Option<&V> {
-        self.store
-            .get(&key.id)
-            .map(|v| v.downcast_ref::<V>().expect("Ensured by the key type."))
+        if key.tag != self.tag {
+            return None;
+        }
+        self.store.get(&key.id).and_then(|v| v.downcast_ref::<V>())
     }
 
     /// Gets a mutable reference to a value of a type `V` from the value store.
     /// Learn more in the documentation for [`Key`].
+    ///
+    /// Returns `None` if the value has been removed, or if the key was issued by another state.
     #[must_use]
     pub fn get_mut<V: 'static>(&mut self, key: Key<V>) -> Option<&mut V> {
+        if key.tag != self.tag {
+            return None;
+        }
         self.store
             .get_mut(&key.id)
-            .map(|v| v.downcast_mut::<V>().expect("Ensured by the key type."))
+            .and_then(|v| v.downcast_mut::<V>())
+    }
+
+    /// Returns `true` if the key was issued by this state, regardless of whether
+    /// the value is still present.
+    #[must_use]
+    pub fn owns<V>(&self, key: Key<V>) -> bool {
+        key.tag == self.tag
+    }
+
+    /// Inserts a statistic, such as [`Tally`](crate::Tally), to the value store and registers it
+    /// under `name`, so that it is included in [`State::report`]. The returned key can be used
+    /// to access the statistic like any other value.
+    ///
+    /// Registering another statistic under the same name replaces the previous registration.
+    ///
+    /// ```
+    /// # use simrs::{State, Tally};
+    /// let mut state = State::default();
+    /// let waiting_time = state.register("waiting_time", Tally::default());
+    /// state.get_mut(waiting_time).unwrap().add(2.0);
+    /// assert_eq!(state.report()["waiting_time.mean"], 2.0);
+    /// ```
+    #[must_use = "Discarding key results in leaking inserted value"]
+    pub fn register<S: Statistic>(&mut self, name: impl Into<String>, statistic: S) -> Key<S> {
+        let key = self.insert(statistic);
+        self.statistics.insert(
+            name.into(),
+            RegisteredStatistic {
+                id: key.id,
+                summary: summary::<S>,
+            },
+        );
+        key
+    }
+
+    /// Returns the summaries of all registered statistics, ordered by name.
+    ///
+    /// Each value of a summary is reported as `"{name}.{value}"`, e.g., `"waiting_time.mean"`.
+    /// Statistics that have been removed from the value store are skipped.
+    #[must_use]
+    pub fn report(&self) -> BTreeMap<String, f64> {
+        let mut report = BTreeMap::new();
+        for (name, statistic) in &self.statistics {
+            if let Some(value) = self.store.get(&statistic.id) {
+                for (label, value) in (statistic.summary)(value.as_ref()) {
+                    report.insert(format!("{name}.{label}"), value);
+                }
+            }
+        }
+        report
     }
 
     /// Creates a new unbounded queue, returning its ID.
-    pub fn add_queue<Q: Queue + 'static>(&mut self, queue: Q) -> QueueId<Q> {
-        let id = self.next_id;
-        self.next_id += 1;
+    pub fn add_queue<Q: Queue + MaybeSend + 'static>(&mut self, queue: Q) -> QueueId<Q> {
+        let id = self.next_queue_id;
+        self.next_queue_id += 1;
         self.queues.insert(id, Box::new(queue));
-        QueueId::new(id)
+        QueueId::new(self.tag, id)
     }
 
     /// Sends `value` to the `queue`. This is a shorthand for `queue_mut(queue).push(value)`.
     ///
+    /// Returns the element evicted to make room for `value`, if any, see [`Queue::push`].
+    ///
     /// # Errors
-    /// It returns an error if the queue is full.
+    /// It returns an error carrying the rejected element if the queue is full, see [`PushError`].
     pub fn send<Q: Queue + 'static>(
         &mut self,
         queue: QueueId<Q>,
         value: Q::Item,
-    ) -> Result<(), PushError> {
+    ) -> Result<Option<Q::Item>, PushError<Q::Item>> {
         self.queue_mut(queue).push(value)
     }
 
@@ -80,30 +237,78 @@ impl State {
     }
 
     /// Returns a immutable reference to the queue by the given ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue does not exist, e.g., when the ID was issued by another state.
+    /// See [`State::try_queue`] for a fallible version.
     #[must_use]
     pub fn queue<Q: Queue + 'static>(&self, queue: QueueId<Q>) -> &Q {
+        self.try_queue(queue)
+            .unwrap_or_else(|err| panic!("Invalid queue ID: {}", err))
+    }
+
+    /// Returns a mutable reference to the queue by the given ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue does not exist, e.g., when the ID was issued by another state.
+    /// See [`State::try_queue_mut`] for a fallible version.
+    #[must_use]
+    pub fn queue_mut<Q: Queue + 'static>(&mut self, queue: QueueId<Q>) -> &mut Q {
+        self.try_queue_mut(queue)
+            .unwrap_or_else(|err| panic!("Invalid queue ID: {}", err))
+    }
+
+    /// Returns a immutable reference to the queue by the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimError::ForeignQueue`] if the ID was issued by another state,
+    /// [`SimError::MissingQueue`] if the queue does not exist, and
+    /// [`SimError::QueueTypeMismatch`] if it has a different type.
+    pub fn try_queue<Q: Queue + 'static>(&self, queue: QueueId<Q>) -> Result<&Q, SimError> {
+        self.check_queue_tag(queue)?;
         self.queues
             .get(&queue.id)
-            .expect("Queues cannot be removed so it must exist.")
+            .ok_or(SimError::MissingQueue { queue: queue.id })?
             .downcast_ref::<Q>()
-            .expect("Ensured by the key type.")
+            .ok_or(SimError::QueueTypeMismatch { queue: queue.id })
     }
 
     /// Returns a mutable reference to the queue by the given ID.
-    #[must_use]
-    pub fn queue_mut<Q: Queue + 'static>(&mut self, queue: QueueId<Q>) -> &mut Q {
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimError::ForeignQueue`] if the ID was issued by another state,
+    /// [`SimError::MissingQueue`] if the queue does not exist, and
+    /// [`SimError::QueueTypeMismatch`] if it has a different type.
+    pub fn try_queue_mut<Q: Queue + 'static>(
+        &mut self,
+        queue: QueueId<Q>,
+    ) -> Result<&mut Q, SimError> {
+        self.check_queue_tag(queue)?;
         self.queues
             .get_mut(&queue.id)
-            .expect("Queues cannot be removed so it must exist.")
+            .ok_or(SimError::MissingQueue { queue: queue.id })?
             .downcast_mut::<Q>()
-            .expect("Ensured by the key type.")
+            .ok_or(SimError::QueueTypeMismatch { queue: queue.id })
+    }
+
+    fn check_queue_tag<Q>(&self, queue: QueueId<Q>) -> Result<(), SimError> {
+        if queue.tag == self.tag {
+            Ok(())
+        } else {
+            Err(SimError::ForeignQueue { queue: queue.id })
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{Fifo, PriorityQueue};
+    use crate::{ComponentId, Fifo, Histogram, PriorityQueue, Tally};
+    use rand_core::RngCore;
 
     #[test]
     fn test_add_remove_key_values() {
@@ -194,4 +399,116 @@ mod test {
         assert_eq!(state.recv(qid), Some(1));
         assert_eq!(state.recv(qid), None);
     }
+
+    #[test]
+    fn test_try_queue() {
+        let mut state = State::default();
+        let fifo = state.add_queue(Fifo::<i32>::default());
+        assert!(state.try_queue(fifo).unwrap().is_empty());
+        state.try_queue_mut(fifo).unwrap().push(1).unwrap();
+        assert_eq!(state.len(fifo), 1);
+
+        let missing = QueueId::<Fifo<i32>>::new(state.tag, 1);
+        assert!(matches!(
+            state.try_queue(missing),
+            Err(SimError::MissingQueue { queue: 1 })
+        ));
+        let mistyped = QueueId::<Fifo<String>>::new(state.tag, 0);
+        assert!(matches!(
+            state.try_queue_mut(mistyped),
+            Err(SimError::QueueTypeMismatch { queue: 0 })
+        ));
+
+        let mut other = State::default();
+        let other_fifo = other.add_queue(Fifo::<i32>::default());
+        assert_eq!(other_fifo.id, fifo.id);
+        assert!(matches!(
+            state.try_queue(other_fifo),
+            Err(SimError::ForeignQueue { queue: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_foreign_keys() {
+        let mut state = State::default();
+        let mut other = State::default();
+        let key = state.insert(1);
+        let other_key = other.insert(2);
+        assert_eq!(key.id, other_key.id);
+        assert!(state.owns(key));
+        assert!(!state.owns(other_key));
+        assert_eq!(state.get(other_key), None);
+        assert_eq!(state.get_mut(other_key), None);
+        assert_eq!(state.remove(other_key), None);
+        assert_eq!(other.get(other_key), Some(&2));
+        assert_eq!(state.remove(key), Some(1));
+    }
+
+    fn draw(state: &mut State, key: impl Into<StreamKey>) -> Vec<u64> {
+        let stream = state.rng_stream(key);
+        (0..5).map(|_| stream.next_u64()).collect()
+    }
+
+    #[test]
+    fn test_rng_streams_reproducible() {
+        let mut state = State::with_seed(42);
+        let mut other = State::with_seed(42);
+        assert_eq!(state.seed(), 42);
+        assert_eq!(draw(&mut state, "a"), draw(&mut other, "a"));
+        assert_eq!(draw(&mut state, "a"), draw(&mut other, "a"));
+
+        let mut reseeded = State::with_seed(43);
+        assert_ne!(
+            draw(&mut State::with_seed(42), "a"),
+            draw(&mut reseeded, "a")
+        );
+    }
+
+    #[test]
+    fn test_rng_streams_independent() {
+        let mut state = State::with_seed(1);
+        let mut other = State::with_seed(1);
+        let component = ComponentId::<()>::new(state.tag, 0);
+        let expected_a = draw(&mut state, "a");
+        let expected_component = draw(&mut state, component);
+        assert_ne!(expected_a, draw(&mut state, "b"));
+        assert_ne!(expected_a, expected_component);
+
+        // New streams, and draws from them, do not affect the existing ones.
+        let tag = other.tag;
+        let _ = draw(&mut other, "b");
+        let _ = draw(&mut other, ComponentId::<()>::new(tag, 1));
+        assert_eq!(draw(&mut other, String::from("a")), expected_a);
+        assert_eq!(
+            draw(&mut other, ComponentId::<()>::new(tag, 0)),
+            expected_component
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_registered_statistics() {
+        let mut state = State::default();
+        let waiting = state.register("waiting", Tally::default());
+        let sojourn = state.register(String::from("sojourn"), Histogram::new(0.0, 10.0, 10));
+        state.get_mut(waiting).unwrap().add(1.0);
+        state.get_mut(waiting).unwrap().add(3.0);
+        state.get_mut(sojourn).unwrap().add(4.0);
+
+        let report = state.report();
+        assert_eq!(report["waiting.count"], 2.0);
+        assert_eq!(report["waiting.mean"], 2.0);
+        assert_eq!(report["sojourn.count"], 1.0);
+        assert_eq!(report["sojourn.p95"], 4.0);
+        assert_eq!(
+            report.keys().next().map(String::as_str),
+            Some("sojourn.count")
+        );
+
+        assert!(state.remove(sojourn).is_some());
+        assert!(state
+            .report()
+            .keys()
+            .all(|name| name.starts_with("waiting.")));
+    }
 }
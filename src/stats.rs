@@ -0,0 +1,728 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::{ClockRef, MaybeSend, OrderedF64, SimTime};
+
+/// Statistic that can be registered in the [`State`](crate::State) under a name,
+/// see [`State::register`](crate::State::register).
+pub trait Statistic: MaybeSend + 'static {
+    /// Returns the named values summarizing the statistic, e.g., `("mean", 1.5)`.
+    fn summary(&self) -> Vec<(String, f64)>;
+}
+
+/// Summary statistics of a sequence of observations, such as waiting times of customers.
+///
+/// The mean and variance are updated using Welford's algorithm, which is numerically stable,
+/// and no observations are stored. `NaN` observations are only counted (see [`Tally::nan`])
+/// and excluded from the other statistics, so that a single one does not poison them.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::Tally;
+/// let mut waiting_time = Tally::default();
+/// for &time in &[2.0, 4.0, 6.0] {
+///     waiting_time.add(time);
+/// }
+/// assert_eq!(waiting_time.count(), 3);
+/// assert_eq!(waiting_time.mean(), 4.0);
+/// assert_eq!(waiting_time.variance(), 4.0);
+/// assert_eq!((waiting_time.min(), waiting_time.max()), (2.0, 6.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tally {
+    count: u64,
+    nan: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Tally {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            nan: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Tally {
+    /// Records an observation. A `NaN` observation is only counted, see [`Tally::nan`].
+    #[allow(clippy::cast_precision_loss)]
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            self.nan += 1;
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Discards all observations, e.g., at the end of a warm-up period.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the number of observations, excluding the `NaN` ones.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the number of `NaN` observations, which are not included in any other statistic.
+    #[must_use]
+    pub fn nan(&self) -> u64 {
+        self.nan
+    }
+
+    /// Returns the sample mean. It is `NaN` if there are no observations.
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.mean
+        }
+    }
+
+    /// Returns the unbiased sample variance. It is `NaN` if there are fewer than two observations.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            f64::NAN
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// Returns the sample standard deviation.
+    #[must_use]
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Returns the minimum observation. It is `NaN` if there are no observations.
+    #[must_use]
+    pub fn min(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.min
+        }
+    }
+
+    /// Returns the maximum observation. It is `NaN` if there are no observations.
+    #[must_use]
+    pub fn max(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.max
+        }
+    }
+}
+
+impl Statistic for Tally {
+    #[allow(clippy::cast_precision_loss)]
+    fn summary(&self) -> Vec<(String, f64)> {
+        vec![
+            (String::from("count"), self.count as f64),
+            (String::from("mean"), self.mean()),
+            (String::from("variance"), self.variance()),
+            (String::from("min"), self.min()),
+            (String::from("max"), self.max()),
+        ]
+    }
+}
+
+/// Estimator of a single quantile using the P² algorithm of Jain and Chlamtac,
+/// which keeps only five markers instead of all observations.
+#[derive(Debug, Clone, PartialEq)]
+struct P2Quantile {
+    p: f64,
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    fn new(p: f64) -> Self {
+        Self {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..5).find(|&i| value < self.heights[i]).unwrap_or(4) - 1
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(&self.increments) {
+            *desired += increment;
+        }
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            if (offset >= 1.0 && self.positions[i + 1] - self.positions[i] > 1.0)
+                || (offset <= -1.0 && self.positions[i - 1] - self.positions[i] < -1.0)
+            {
+                let direction = offset.signum();
+                let height = self.parabolic(i, direction);
+                self.heights[i] = if self.heights[i - 1] < height && height < self.heights[i + 1] {
+                    height
+                } else {
+                    self.linear(i, direction)
+                };
+                self.positions[i] += direction;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let neighbor = if d > 0.0 { i + 1 } else { i - 1 };
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d * (q[neighbor] - q[i]) / (n[neighbor] - n[i])
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn estimate(&self) -> f64 {
+        match self.count {
+            0 => f64::NAN,
+            count if count < 5 => {
+                let mut values = self.heights[..count].to_vec();
+                values.sort_by(f64::total_cmp);
+                values[(self.p * (count - 1) as f64).round() as usize]
+            }
+            _ => self.heights[2],
+        }
+    }
+}
+
+/// Histogram of observations with equally wide bins, which also estimates a number of quantiles.
+///
+/// Observations below or above the range of the bins are counted as underflow and overflow,
+/// respectively. `NaN` observations are only counted (see [`Histogram::nan`]) and excluded
+/// from the other statistics. The quantiles are estimated with the P² algorithm, which does not store
+/// the observations. By default, the quantiles 0.5, 0.9, 0.95, and 0.99 are estimated,
+/// which can be changed with [`Histogram::with_quantiles`].
+///
+/// # Example
+///
+/// ```
+/// # use simrs::Histogram;
+/// let mut sojourn_time = Histogram::new(0.0, 10.0, 5);
+/// for value in 0..10 {
+///     sojourn_time.add(f64::from(value) + 0.5);
+/// }
+/// sojourn_time.add(12.0);
+/// assert_eq!(sojourn_time.bins()[0], (0.0, 2.0, 2));
+/// assert_eq!(sojourn_time.overflow(), 1);
+/// assert_eq!(sojourn_time.tally().count(), 11);
+/// assert!((sojourn_time.quantile(0.5).unwrap() - 5.5).abs() < 1.0);
+/// assert!(sojourn_time.quantile(0.3).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    low: f64,
+    width: f64,
+    counts: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+    tally: Tally,
+    quantiles: Vec<P2Quantile>,
+}
+
+impl Histogram {
+    /// Creates a histogram with `bins` equally wide bins covering the range `[low, high)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bins` is zero or if `low` is not less than `high`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(low: f64, high: f64, bins: usize) -> Self {
+        assert!(bins > 0, "Histogram must have at least one bin.");
+        assert!(low < high, "Histogram range must not be empty.");
+        Self {
+            low,
+            width: (high - low) / bins as f64,
+            counts: vec![0; bins],
+            underflow: 0,
+            overflow: 0,
+            tally: Tally::default(),
+            quantiles: Vec::new(),
+        }
+        .with_quantiles([0.5, 0.9, 0.95, 0.99].iter().copied())
+    }
+
+    /// Replaces the estimated quantiles with the given ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the quantiles is not in the open interval `(0, 1)`.
+    #[must_use]
+    pub fn with_quantiles<I: IntoIterator<Item = f64>>(mut self, quantiles: I) -> Self {
+        self.quantiles = quantiles
+            .into_iter()
+            .map(|p| {
+                assert!(p > 0.0 && p < 1.0, "Quantile must be between 0 and 1.");
+                P2Quantile::new(p)
+            })
+            .collect();
+        self
+    }
+
+    /// Records an observation. A `NaN` observation is only counted, see [`Histogram::nan`].
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn add(&mut self, value: f64) {
+        self.tally.add(value);
+        if value.is_nan() {
+            return;
+        }
+        for quantile in &mut self.quantiles {
+            quantile.add(value);
+        }
+        let bin = ((value - self.low) / self.width).floor();
+        if bin < 0.0 {
+            self.underflow += 1;
+        } else if bin >= self.counts.len() as f64 {
+            self.overflow += 1;
+        } else {
+            self.counts[bin as usize] += 1;
+        }
+    }
+
+    /// Discards all observations, keeping the bins and the estimated quantiles.
+    pub fn reset(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.underflow = 0;
+        self.overflow = 0;
+        self.tally.reset();
+        for quantile in &mut self.quantiles {
+            *quantile = P2Quantile::new(quantile.p);
+        }
+    }
+
+    /// Returns the bins as `(low, high, count)` triples.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn bins(&self) -> Vec<(f64, f64, u64)> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(idx, &count)| {
+                let low = self.low + self.width * idx as f64;
+                (low, low + self.width, count)
+            })
+            .collect()
+    }
+
+    /// Returns the number of observations below the range of the bins.
+    #[must_use]
+    pub fn underflow(&self) -> u64 {
+        self.underflow
+    }
+
+    /// Returns the number of observations at or above the range of the bins.
+    #[must_use]
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    /// Returns the number of `NaN` observations, which are not included in any other statistic.
+    #[must_use]
+    pub fn nan(&self) -> u64 {
+        self.tally.nan()
+    }
+
+    /// Returns the summary statistics of all observations.
+    #[must_use]
+    pub fn tally(&self) -> &Tally {
+        &self.tally
+    }
+
+    /// Returns the estimate of the quantile `p`, or `None` if it is not estimated
+    /// by this histogram. The estimate is `NaN` if there are no observations.
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn quantile(&self, p: f64) -> Option<f64> {
+        self.quantiles
+            .iter()
+            .find(|quantile| quantile.p == p)
+            .map(P2Quantile::estimate)
+    }
+}
+
+impl Statistic for Histogram {
+    fn summary(&self) -> Vec<(String, f64)> {
+        let mut summary = self.tally.summary();
+        summary.extend(self.quantiles.iter().map(|quantile| {
+            let percent = (quantile.p * 1e6).round() / 1e4;
+            (format!("p{percent}"), quantile.estimate())
+        }));
+        summary
+    }
+}
+
+/// Time-weighted statistic of a piecewise constant value, such as a queue length or
+/// the number of busy servers.
+///
+/// Every update is recorded at the current simulation time, as read from a [`ClockRef`],
+/// and the previous value is weighted by the time it was held. The statistic is typically
+/// inserted into the [`State`](crate::State) and updated by the components whenever
+/// the value changes. Time is measured in the units of [`SimTime::to_f64`], e.g., seconds
+/// for [`Duration`].
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{Component, ComponentId, Scheduler, Simulation, State, TimeWeighted};
+/// # use std::time::Duration;
+/// # struct Idle;
+/// # impl Component for Idle {
+/// #     type Event = ();
+/// #     fn process_event(&self, _: ComponentId<()>, _: &(), _: &mut Scheduler, _: &mut State) {}
+/// # }
+/// let mut simulation = Simulation::default();
+/// let component = simulation.add_component(Idle);
+/// let clock = simulation.scheduler.clock();
+/// let queue_length = simulation.state.insert(TimeWeighted::new(clock, 0.0));
+/// simulation.schedule(Duration::from_secs(1), component, ());
+/// simulation.schedule(Duration::from_secs(4), component, ());
+/// simulation.step();
+/// simulation.state.get_mut(queue_length).unwrap().update(2.0);
+/// simulation.step();
+/// let queue_length = simulation.state.get(queue_length).unwrap();
+/// assert_eq!(queue_length.mean(), 1.5);
+/// assert_eq!(queue_length.time_at(2.0), 3.0);
+/// ```
+pub struct TimeWeighted<T = Duration> {
+    clock: ClockRef<T>,
+    start: T,
+    last_update: T,
+    value: f64,
+    area: f64,
+    min: f64,
+    max: f64,
+    levels: BTreeMap<OrderedF64, f64>,
+}
+
+impl<T: SimTime> TimeWeighted<T> {
+    /// Creates a new statistic starting at the current simulation time with the `initial` value.
+    #[must_use]
+    pub fn new(clock: ClockRef<T>, initial: f64) -> Self {
+        let now = clock.time();
+        Self {
+            clock,
+            start: now,
+            last_update: now,
+            value: initial,
+            area: 0.0,
+            min: initial,
+            max: initial,
+            levels: BTreeMap::new(),
+        }
+    }
+
+    /// Changes the value at the current simulation time.
+    pub fn update(&mut self, value: f64) {
+        self.accumulate();
+        self.value = value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Adds `delta` to the current value, e.g., `1.0` when an element is pushed to a queue.
+    pub fn add(&mut self, delta: f64) {
+        self.update(self.value + delta);
+    }
+
+    /// Discards all observations made so far, e.g., at the end of a warm-up period.
+    /// The statistic starts over at the current time with the current value.
+    pub fn reset(&mut self) {
+        let now = self.clock.time();
+        self.start = now;
+        self.last_update = now;
+        self.area = 0.0;
+        self.min = self.value;
+        self.max = self.value;
+        self.levels.clear();
+    }
+
+    /// Returns the current value.
+    #[must_use]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Returns the minimum value observed since the start or the last reset.
+    #[must_use]
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the maximum value observed since the start or the last reset.
+    #[must_use]
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Returns the time elapsed since the start or the last reset.
+    #[must_use]
+    pub fn elapsed(&self) -> f64 {
+        self.clock.time().to_f64() - self.start.to_f64()
+    }
+
+    /// Returns the time-average of the value up to the current time.
+    /// If no time has elapsed, the current value is returned.
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        let elapsed = self.elapsed();
+        if elapsed > 0.0 {
+            (self.area + self.value * self.pending()) / elapsed
+        } else {
+            self.value
+        }
+    }
+
+    /// Returns the total time during which the value was equal to `level`.
+    #[must_use]
+    pub fn time_at(&self, level: f64) -> f64 {
+        let recorded = self.levels.get(&OrderedF64(level)).copied().unwrap_or(0.0);
+        if OrderedF64(level) == OrderedF64(self.value) {
+            recorded + self.pending()
+        } else {
+            recorded
+        }
+    }
+
+    /// Returns the total time spent at each level, ordered by the level.
+    /// Levels at which no time was spent are omitted.
+    #[must_use]
+    pub fn levels(&self) -> Vec<(f64, f64)> {
+        let mut levels = self.levels.clone();
+        *levels.entry(OrderedF64(self.value)).or_insert(0.0) += self.pending();
+        levels
+            .into_iter()
+            .filter(|(_, time)| *time > 0.0)
+            .map(|(level, time)| (level.get(), time))
+            .collect()
+    }
+
+    /// Time for which the current value has been held since the last update.
+    fn pending(&self) -> f64 {
+        self.clock.time().to_f64() - self.last_update.to_f64()
+    }
+
+    /// Records the time for which the current value has been held.
+    fn accumulate(&mut self) {
+        let held = self.pending();
+        if held > 0.0 {
+            self.area += self.value * held;
+            *self.levels.entry(OrderedF64(self.value)).or_insert(0.0) += held;
+        }
+        self.last_update = self.clock.time();
+    }
+}
+
+impl<T: SimTime> Statistic for TimeWeighted<T> {
+    fn summary(&self) -> Vec<(String, f64)> {
+        vec![
+            (String::from("mean"), self.mean()),
+            (String::from("min"), self.min()),
+            (String::from("max"), self.max()),
+            (String::from("elapsed"), self.elapsed()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sync::Clock;
+    use rand_core::RngCore;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_time_weighted() {
+        let clock = Clock::new(0_u64);
+        let mut stat = TimeWeighted::new(ClockRef::from(clock.clone()), 1.0);
+        assert_eq!(stat.mean(), 1.0);
+
+        clock.set(2);
+        stat.update(3.0);
+        clock.set(3);
+        stat.add(-3.0);
+        // Zero-length levels are not recorded.
+        stat.update(5.0);
+        stat.update(0.0);
+        clock.set(6);
+
+        assert_eq!(stat.elapsed(), 6.0);
+        assert_eq!(stat.mean(), (2.0 + 3.0) / 6.0);
+        assert_eq!(stat.min(), 0.0);
+        assert_eq!(stat.max(), 5.0);
+        assert_eq!(stat.value(), 0.0);
+        assert_eq!(stat.time_at(0.0), 3.0);
+        assert_eq!(stat.time_at(1.0), 2.0);
+        assert_eq!(stat.time_at(5.0), 0.0);
+        assert_eq!(stat.levels(), vec![(0.0, 3.0), (1.0, 2.0), (3.0, 1.0)]);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_reset() {
+        let clock = Clock::new(Duration::ZERO);
+        let mut stat = TimeWeighted::new(ClockRef::from(clock.clone()), 10.0);
+        clock.set(Duration::from_secs(5));
+        stat.update(2.0);
+        stat.reset();
+        assert_eq!(stat.elapsed(), 0.0);
+        assert_eq!(stat.mean(), 2.0);
+        assert_eq!((stat.min(), stat.max()), (2.0, 2.0));
+        assert!(stat.levels().is_empty());
+
+        clock.set(Duration::from_secs(7));
+        stat.update(4.0);
+        clock.set(Duration::from_secs(8));
+        assert_eq!(stat.mean(), (2.0 * 2.0 + 4.0) / 3.0);
+        assert_eq!(stat.levels(), vec![(2.0, 2.0), (4.0, 1.0)]);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_tally() {
+        let mut tally = Tally::default();
+        assert_eq!(tally.count(), 0);
+        assert!(tally.mean().is_nan());
+        assert!(tally.min().is_nan());
+        tally.add(1.0);
+        assert_eq!(tally.mean(), 1.0);
+        assert!(tally.variance().is_nan());
+        for &value in &[2.0, 3.0, 4.0, 5.0] {
+            tally.add(value);
+        }
+        assert_eq!(tally.count(), 5);
+        assert_eq!(tally.mean(), 3.0);
+        assert_eq!(tally.variance(), 2.5);
+        assert_eq!((tally.min(), tally.max()), (1.0, 5.0));
+        tally.add(f64::NAN);
+        assert_eq!(tally.nan(), 1);
+        assert_eq!(tally.count(), 5);
+        assert_eq!(tally.mean(), 3.0);
+        assert_eq!(tally.variance(), 2.5);
+        assert_eq!((tally.min(), tally.max()), (1.0, 5.0));
+        tally.reset();
+        assert_eq!(tally, Tally::default());
+    }
+
+    #[test]
+    fn test_p2_quantile() {
+        let mut median = P2Quantile::new(0.5);
+        assert!(median.estimate().is_nan());
+        for &value in &[3.0, 1.0, 2.0] {
+            median.add(value);
+        }
+        assert!((median.estimate() - 2.0).abs() < f64::EPSILON);
+
+        let mut state = crate::State::with_seed(11);
+        let rng = state.rng_stream("values");
+        let values: Vec<f64> = (0..10_000)
+            .map(|_| f64::from(rng.next_u32() % 1000))
+            .collect();
+        for &p in &[0.1, 0.5, 0.9, 0.99] {
+            let mut quantile = P2Quantile::new(p);
+            for &value in &values {
+                quantile.add(value);
+            }
+            let expected = p * 1000.0;
+            assert!(
+                (quantile.estimate() - expected).abs() < 10.0,
+                "{} is not close to {}",
+                quantile.estimate(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_histogram() {
+        let mut histogram = Histogram::new(0.0, 1.0, 4).with_quantiles(vec![0.5]);
+        for &value in &[-1.0, 0.1, 0.3, 0.3, 0.99, 1.0, f64::NAN] {
+            histogram.add(value);
+        }
+        assert_eq!(
+            histogram.bins(),
+            vec![
+                (0.0, 0.25, 1),
+                (0.25, 0.5, 2),
+                (0.5, 0.75, 0),
+                (0.75, 1.0, 1)
+            ]
+        );
+        assert_eq!(histogram.underflow(), 1);
+        assert_eq!(histogram.overflow(), 1);
+        assert_eq!(histogram.nan(), 1);
+        assert_eq!(histogram.tally().count(), 6);
+        assert!(!histogram.tally().mean().is_nan());
+        assert!(!histogram.quantile(0.5).unwrap().is_nan());
+        assert!(histogram.quantile(0.9).is_none());
+        let summary = histogram.summary();
+        assert_eq!(summary.last().unwrap().0, "p50");
+
+        histogram.reset();
+        assert!(histogram.bins().iter().all(|&(_, _, count)| count == 0));
+        assert_eq!(histogram.nan(), 0);
+        assert!(histogram.quantile(0.5).unwrap().is_nan());
+    }
+}
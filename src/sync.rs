@@ -0,0 +1,184 @@
+use std::any::Any;
+use std::error::Error;
+
+#[cfg(feature = "sync")]
+use std::sync::{Arc, Mutex, PoisonError};
+#[cfg(not(feature = "sync"))]
+use std::{cell::Cell, rc::Rc};
+
+/// Bound required for all values stored in a simulation: components, events, queues,
+/// values in the state, and so on.
+///
+/// By default, it is implemented for all types. With the `sync` feature enabled, it is
+/// equivalent to [`Send`], which makes [`Simulation`](crate::Simulation) itself `Send`,
+/// so that it can be moved to another thread, e.g., to run independent replications in parallel.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Bound required for all values stored in a simulation: components, events, queues,
+/// values in the state, and so on.
+///
+/// By default, it is implemented for all types. With the `sync` feature enabled, it is
+/// equivalent to [`Send`], which makes [`Simulation`](crate::Simulation) itself `Send`,
+/// so that it can be moved to another thread, e.g., to run independent replications in parallel.
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "sync")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Type-erased value stored in a simulation.
+#[cfg(not(feature = "sync"))]
+pub(crate) type AnyBox = Box<dyn Any>;
+
+/// Type-erased value stored in a simulation.
+#[cfg(feature = "sync")]
+pub(crate) type AnyBox = Box<dyn Any + Send>;
+
+/// Factory of type-erased events of a periodic event.
+#[cfg(not(feature = "sync"))]
+pub(crate) type EventFactory = Box<dyn FnMut() -> AnyBox>;
+
+/// Factory of type-erased events of a periodic event.
+#[cfg(feature = "sync")]
+pub(crate) type EventFactory = Box<dyn FnMut() -> AnyBox + Send>;
+
+/// Type-erased error returned by a component.
+#[cfg(not(feature = "sync"))]
+pub(crate) type ErrorBox = Box<dyn Error>;
+
+/// Type-erased error returned by a component.
+#[cfg(feature = "sync")]
+pub(crate) type ErrorBox = Box<dyn Error + Send>;
+
+/// Shared simulation clock. It is written by the scheduler and read by [`ClockRef`](crate::ClockRef).
+///
+/// The scheduler keeps its own copy of the time and updates the shared clock only while
+/// it is actually shared (see [`Clock::is_shared`]), so that the lock taken with the `sync`
+/// feature is not on the hot path of a simulation without any `ClockRef`.
+pub(crate) struct Clock<T> {
+    #[cfg(not(feature = "sync"))]
+    time: Rc<Cell<T>>,
+    #[cfg(feature = "sync")]
+    time: Arc<Mutex<T>>,
+}
+
+impl<T> Clone for Clock<T> {
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(not(feature = "sync"))]
+            time: Rc::clone(&self.time),
+            #[cfg(feature = "sync")]
+            time: Arc::clone(&self.time),
+        }
+    }
+}
+
+#[cfg(not(feature = "sync"))]
+impl<T: Copy> Clock<T> {
+    pub(crate) fn new(time: T) -> Self {
+        Self {
+            time: Rc::new(Cell::new(time)),
+        }
+    }
+
+    pub(crate) fn get(&self) -> T {
+        self.time.get()
+    }
+
+    pub(crate) fn set(&self, time: T) {
+        self.time.set(time);
+    }
+
+    /// Returns `true` if there is another handle to this clock.
+    pub(crate) fn is_shared(&self) -> bool {
+        Rc::strong_count(&self.time) > 1
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: Copy> Clock<T> {
+    pub(crate) fn new(time: T) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    pub(crate) fn get(&self) -> T {
+        *self.time.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn set(&self, time: T) {
+        *self.time.lock().unwrap_or_else(PoisonError::into_inner) = time;
+    }
+
+    /// Returns `true` if there is another handle to this clock.
+    pub(crate) fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.time) > 1
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    use crate::{
+        Component, ComponentId, Executor, Fifo, Key, QueueId, Scheduler, Simulation, State,
+    };
+    use std::thread;
+    use std::time::Duration;
+
+    struct Producer {
+        outgoing: QueueId<Fifo<usize>>,
+        produced: Key<usize>,
+    }
+
+    impl Component for Producer {
+        type Event = ();
+
+        fn process_event(
+            &self,
+            self_id: ComponentId<()>,
+            _event: &(),
+            scheduler: &mut Scheduler,
+            state: &mut State,
+        ) {
+            let produced = state.get_mut(self.produced).unwrap();
+            *produced += 1;
+            let produced = *produced;
+            state.send(self.outgoing, produced).unwrap();
+            if produced < 10 {
+                scheduler.schedule(Duration::from_secs(1), self_id, ());
+            }
+        }
+    }
+
+    fn assert_send<S: Send>(_: &S) {}
+
+    #[test]
+    fn test_run_on_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut simulation = Simulation::default();
+                let outgoing = simulation.add_queue(Fifo::default());
+                let counter = simulation.state.insert(0);
+                let component = simulation.add_component(Producer {
+                    outgoing,
+                    produced: counter,
+                });
+                simulation.schedule(Duration::default(), component, ());
+                assert_send(&simulation);
+                let clock = simulation.scheduler.clock();
+                thread::spawn(move || {
+                    simulation.execute(Executor::unbound()).unwrap();
+                    assert_eq!(clock.time(), Duration::from_secs(9));
+                    (simulation.state.len(outgoing), simulation.scheduler.time())
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), (10, Duration::from_secs(9)));
+        }
+    }
+}
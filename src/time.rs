@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Add;
+use std::time::Duration;
+
+use crate::MaybeSend;
+
+/// Trait implemented by types that can represent the simulation time.
+///
+/// The time must be totally ordered and it must be possible to add two time values,
+/// e.g., the current time and a delay. The [`Default`] value is the beginning of the simulation.
+/// Additionally, the time must be convertible to `f64`, which is used by some event queues,
+/// such as [`CalendarQueue`](crate::CalendarQueue), to distribute events among buckets.
+///
+/// The following implementations are provided:
+/// - [`Duration`] (the default everywhere in the library),
+/// - `u64` for models measured in integer ticks,
+/// - [`OrderedF64`] for models measured in arbitrary real units, such as days.
+///
+/// Delays passed to the [`Scheduler`](crate::Scheduler) must not move the clock backwards.
+/// Therefore, scheduling an event with a delay that is negative or NaN, which is only possible
+/// with [`OrderedF64`], panics.
+///
+/// # Example
+///
+/// ```
+/// # use simrs::{Component, ComponentId, Scheduler, Simulation, State};
+/// struct Ticker;
+///
+/// impl Component<u64> for Ticker {
+///     type Event = ();
+///     fn process_event(
+///         &self,
+///         self_id: ComponentId<()>,
+///         _event: &(),
+///         scheduler: &mut Scheduler<u64>,
+///         _state: &mut State,
+///     ) {
+///         if scheduler.time() < 10 {
+///             scheduler.schedule(3, self_id, ());
+///         }
+///     }
+/// }
+///
+/// let mut simulation = Simulation::<u64>::new();
+/// let ticker = simulation.add_component(Ticker);
+/// simulation.schedule(0, ticker, ());
+/// while simulation.step() {}
+/// assert_eq!(simulation.scheduler.time(), 12);
+/// ```
+pub trait SimTime:
+    Copy + Ord + Default + fmt::Debug + Add<Output = Self> + MaybeSend + 'static
+{
+    /// Converts the time to a floating point number.
+    ///
+    /// The conversion may be lossy but it must preserve the order,
+    /// i.e., `a <= b` implies `a.to_f64() <= b.to_f64()`.
+    fn to_f64(self) -> f64;
+
+    /// Converts a floating point number in the units of [`SimTime::to_f64`] to the time.
+    ///
+    /// It is used, e.g., by [`ProcessorSharing`](crate::ProcessorSharing) to compute
+    /// the completion times. The conversion may be lossy, and values outside the range
+    /// of the time must saturate instead of panicking, e.g., for [`Duration`], negative
+    /// and NaN values map to zero, and values that are too large map to [`Duration::MAX`].
+    fn from_f64(value: f64) -> Self;
+
+    /// Adds two time values, saturating at the greatest time instead of overflowing.
+    ///
+    /// It is used, e.g., by [`DeadlineQueue`](crate::DeadlineQueue) to compute the expiry times,
+    /// so that an unlimited patience can be represented by the greatest time.
+    /// By default, it is equivalent to `self + other`.
+    #[must_use]
+    fn saturating_add(self, other: Self) -> Self {
+        self + other
+    }
+}
+
+impl SimTime for Duration {
+    fn to_f64(self) -> f64 {
+        self.as_secs_f64()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        if value.is_nan() || value <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::try_from_secs_f64(value).unwrap_or(Duration::MAX)
+        }
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        Duration::saturating_add(self, other)
+    }
+}
+
+impl SimTime for u64 {
+    #[allow(clippy::cast_precision_loss)]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    /// Rounds to the nearest tick.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn from_f64(value: f64) -> Self {
+        // Casting saturates, and NaN is cast to zero.
+        value.round() as u64
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        u64::saturating_add(self, other)
+    }
+}
+
+/// A wrapper over `f64` that implements total ordering, and therefore can be used as [`SimTime`].
+///
+/// The values are ordered according to [`f64::total_cmp`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OrderedF64(pub f64);
+
+impl OrderedF64 {
+    /// Returns the wrapped value.
+    #[must_use]
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl From<f64> for OrderedF64 {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Add for OrderedF64 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl SimTime for OrderedF64 {
+    fn to_f64(self) -> f64 {
+        self.0
+    }
+
+    /// Wraps the value as is.
+    fn from_f64(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ordered_f64() {
+        let a = OrderedF64::from(1.5);
+        let b = OrderedF64(2.0);
+        assert!(a < b);
+        assert_eq!(a + b, OrderedF64(3.5));
+        assert!(((a + b).get() - 3.5).abs() < f64::EPSILON);
+        assert_eq!(OrderedF64::default(), OrderedF64(0.0));
+        assert_eq!(a.max(b), b);
+        assert!(OrderedF64(f64::NAN) > OrderedF64(f64::INFINITY));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_to_f64() {
+        assert_eq!(Duration::from_millis(1500).to_f64(), 1.5);
+        assert_eq!(7_u64.to_f64(), 7.0);
+        assert_eq!(OrderedF64(2.5).to_f64(), 2.5);
+    }
+
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(Duration::from_f64(1.5), Duration::from_millis(1500));
+        assert_eq!(Duration::from_f64(-1.0), Duration::ZERO);
+        assert_eq!(Duration::from_f64(f64::NAN), Duration::ZERO);
+        assert_eq!(Duration::from_f64(f64::INFINITY), Duration::MAX);
+        assert_eq!(u64::from_f64(6.6), 7);
+        assert_eq!(u64::from_f64(-1.0), 0);
+        assert_eq!(u64::from_f64(1e30), u64::MAX);
+        assert_eq!(OrderedF64::from_f64(-2.5), OrderedF64(-2.5));
+    }
+
+    #[test]
+    fn test_saturating_add() {
+        fn add<T: SimTime>(lhs: T, rhs: T) -> T {
+            SimTime::saturating_add(lhs, rhs)
+        }
+        assert_eq!(add(Duration::MAX, Duration::from_secs(1)), Duration::MAX);
+        assert_eq!(add(u64::MAX, 1), u64::MAX);
+        assert_eq!(add(OrderedF64(1.0), OrderedF64(2.0)), OrderedF64(3.0));
+    }
+}